@@ -0,0 +1,498 @@
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::node::Node;
+use crate::optimizations::{
+    CoordinatePrecision, DescriptionElement, Optimization, Optimizations, Preset,
+    TransformPrecision,
+};
+use crate::parser::{Parser, ParserOptions};
+use crate::report::{PassReport, PassSizes};
+use crate::writer::{SVGWriter, WriterOptions};
+use std::io::{self, Read, Write};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Instant;
+
+fn serialized_size(nodes: &[Node]) -> Result<usize> {
+    let mut output = Vec::new();
+    SVGWriter::new(&mut output)
+        .write(nodes)
+        .map_err(Error::Write)?;
+    Ok(output.len())
+}
+
+/// Options for parsing, optimizing and writing documents.
+///
+/// The default configuration matches the defaults of the command line: all optimizations except lossy ones are
+/// enabled and the output is not indented.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub(crate) parser_options: ParserOptions,
+    pub(crate) writer_options: WriterOptions,
+    pub(crate) disable_by_default: bool,
+    pub(crate) multipass: Option<NonZeroUsize>,
+    pub(crate) optimizations: Optimizations,
+}
+
+impl Config {
+    /// Selects the optimizations enabled by default and their parameters.
+    #[must_use]
+    pub fn preset(mut self, preset: Preset) -> Self {
+        self.optimizations.set_preset(preset);
+        self
+    }
+
+    /// Sets the precision of lossy optimizations instead of the one of the preset.
+    #[must_use]
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.optimizations
+            .set_parameters(|parameters| parameters.precision = Some(precision));
+        self
+    }
+
+    /// Sets the precision of numbers rounded by `round_floats`, instead of the one set by [`Config::precision`].
+    #[must_use]
+    pub fn coordinate_precision(mut self, precision: usize) -> Self {
+        self.optimizations.set_parameters(|parameters| {
+            parameters.round_floats = CoordinatePrecision::new(precision);
+        });
+        self
+    }
+
+    /// Sets the precision of matrices created by `merge_transforms`, instead of the one set by [`Config::precision`].
+    #[must_use]
+    pub fn transform_precision(mut self, precision: usize) -> Self {
+        self.optimizations.set_parameters(|parameters| {
+            parameters.merge_transforms = TransformPrecision::new(precision);
+        });
+        self
+    }
+
+    /// Keeps the elements of the given kind in `remove_descriptions`. Can be called multiple times.
+    #[must_use]
+    pub fn keep_description(mut self, element: DescriptionElement) -> Self {
+        self.optimizations.set_parameters(|parameters| {
+            parameters.remove_descriptions =
+                std::mem::take(&mut parameters.remove_descriptions).keep_description(element);
+        });
+        self
+    }
+
+    /// Converts identical paths in `convert_paths_to_uses` only if it saves at least the given number of bytes.
+    #[must_use]
+    pub fn min_use_saving(mut self, bytes: usize) -> Self {
+        self.optimizations.set_parameters(|parameters| {
+            parameters.convert_paths_to_uses =
+                std::mem::take(&mut parameters.convert_paths_to_uses).min_use_saving(bytes);
+        });
+        self
+    }
+
+    /// Keeps the namespace in `remove_editor_namespace_data`, even if it belongs to a known editor. Can be called
+    /// multiple times.
+    #[must_use]
+    pub fn keep_namespace(mut self, uri: &str) -> Self {
+        self.optimizations.set_parameters(|parameters| {
+            parameters.remove_editor_namespace_data =
+                std::mem::take(&mut parameters.remove_editor_namespace_data).keep_namespace(uri);
+        });
+        self
+    }
+
+    /// Removes the namespace in `remove_editor_namespace_data` in addition to the known editor namespaces. Can be
+    /// called multiple times.
+    #[must_use]
+    pub fn remove_namespace(mut self, uri: &str) -> Self {
+        self.optimizations.set_parameters(|parameters| {
+            parameters.remove_editor_namespace_data =
+                std::mem::take(&mut parameters.remove_editor_namespace_data).remove_namespace(uri);
+        });
+        self
+    }
+
+    /// Applies exactly the given optimizations in the given order, ignoring the preset and enabled or disabled ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the names is not a name of an optimization.
+    pub fn passes(mut self, names: &[&str]) -> Result<Self> {
+        self.optimizations.set_passes(names)?;
+        Ok(self)
+    }
+
+    /// Adds a custom optimization, applied after the built-in ones unless ordered with [`Config::passes`], which has to
+    /// be called after registering it. Lossy optimizations are disabled by default, like the built-in ones, and the safe
+    /// preset does not enable custom optimizations, as they may change the structure of the document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an optimization with the same name is already registered.
+    pub fn register(mut self, optimization: impl Optimization + 'static) -> Result<Self> {
+        self.optimizations.register(Arc::new(optimization))?;
+        Ok(self)
+    }
+
+    /// Enables the optimization, even if it is disabled by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a name of an optimization.
+    pub fn enable(mut self, name: &str) -> Result<Self> {
+        self.optimizations.set_enabled(name, true)?;
+        Ok(self)
+    }
+
+    /// Disables the optimization.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a name of an optimization.
+    pub fn disable(mut self, name: &str) -> Result<Self> {
+        self.optimizations.set_enabled(name, false)?;
+        Ok(self)
+    }
+
+    /// Disables all optimizations which are not explicitly enabled.
+    #[must_use]
+    pub fn disable_by_default(mut self) -> Self {
+        self.disable_by_default = true;
+        self
+    }
+
+    /// Applies the optimizations again as long as the output shrinks, at most the given number of times in total.
+    #[must_use]
+    pub fn multipass(mut self, max_iterations: NonZeroUsize) -> Self {
+        self.multipass = Some(max_iterations);
+        self
+    }
+
+    /// Writes each element on a separate line, indented by the given number of spaces per level.
+    #[must_use]
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.writer_options.indent = Some(indent);
+        self
+    }
+
+    /// Keeps the xml declaration and the DOCTYPE, if present.
+    #[must_use]
+    pub fn keep_declarations(mut self) -> Self {
+        self.parser_options.keep_xml_declaration = true;
+        self.parser_options.keep_doctype = true;
+        self
+    }
+
+    /// Parses the document, keeping declarations according to the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid SVG document.
+    pub fn parse(&self, source: &str) -> Result<Document> {
+        Ok(Document {
+            nodes: self.parse_nodes(source.as_bytes())?,
+        })
+    }
+
+    /// Applies the enabled optimizations to the document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document could not be serialized to measure its size for multipass.
+    pub fn optimize(&self, document: &mut Document) -> Result<()> {
+        let (nodes, _) = self.apply_optimizations(std::mem::take(&mut document.nodes), false)?;
+        document.nodes = nodes;
+        Ok(())
+    }
+
+    /// Writes the document according to the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document could not be serialized.
+    pub fn write(&self, document: &Document) -> Result<String> {
+        let mut output = Vec::new();
+        self.write_nodes(&mut output, &document.nodes)?;
+        String::from_utf8(output)
+            .map_err(|error| Error::Write(io::Error::new(io::ErrorKind::InvalidData, error)))
+    }
+
+    pub(crate) fn parse_nodes<R: Read>(&self, source: R) -> Result<Vec<Node>> {
+        Parser::new(source)
+            .and_then(|parser| {
+                parser
+                    .with_options(self.parser_options.clone())
+                    .parse_document()
+            })
+            .map_err(Error::Parse)
+    }
+
+    pub(crate) fn write_nodes<W: Write>(&self, target: W, nodes: &[Node]) -> Result<()> {
+        SVGWriter::new(target)
+            .with_options(self.writer_options.clone())
+            .write(nodes)
+            .map_err(Error::Write)
+    }
+
+    /// Returns the optimized nodes and reports of the applied optimizations.
+    ///
+    /// Sizes are measured only if passes are reported, and the time spent measuring them is not included in timings.
+    pub(crate) fn apply_optimizations(
+        &self,
+        mut nodes: Vec<Node>,
+        report_passes: bool,
+    ) -> Result<(Vec<Node>, Vec<PassReport>)> {
+        let mut size_before = if report_passes {
+            Some(serialized_size(&nodes)?)
+        } else {
+            None
+        };
+        let mut passes = vec![];
+
+        let Some(max_iterations) = self.multipass else {
+            let optimized = self.apply_optimizations_once(nodes, &mut size_before, &mut passes)?;
+            return Ok((optimized, passes));
+        };
+
+        let mut size = serialized_size(&nodes)?;
+        for iteration in 0..max_iterations.get() {
+            let mut iteration_size_before = size_before;
+            let mut iteration_passes = vec![];
+            let optimized = self.apply_optimizations_once(
+                nodes.clone(),
+                &mut iteration_size_before,
+                &mut iteration_passes,
+            )?;
+            let optimized_size = serialized_size(&optimized)?;
+            let shrunk = optimized_size < size;
+
+            // The first iteration is kept regardless, as it would be without multipass. Passes of discarded
+            // iterations are not reported, as they do not change the output.
+            if shrunk || iteration == 0 {
+                nodes = optimized;
+                size = optimized_size;
+                size_before = iteration_size_before;
+                passes.extend(iteration_passes);
+            }
+            if !shrunk {
+                break;
+            }
+        }
+
+        Ok((nodes, passes))
+    }
+
+    /// Appends reports of the applied optimizations to `passes`.
+    ///
+    /// `size_before` is the size of the nodes if passes are reported, and is updated to the size of the result.
+    fn apply_optimizations_once(
+        &self,
+        nodes: Vec<Node>,
+        size_before: &mut Option<usize>,
+        passes: &mut Vec<PassReport>,
+    ) -> Result<Vec<Node>> {
+        let report_passes = size_before.is_some();
+        let mut applied = vec![];
+        let mut pass_start = Instant::now();
+        let optimized =
+            self.optimizations
+                .apply(nodes, !self.disable_by_default, |optimization, nodes| {
+                    let duration = pass_start.elapsed();
+                    let size_after = report_passes.then(|| serialized_size(nodes));
+                    applied.push((
+                        optimization.name(),
+                        optimization.parameters(),
+                        duration,
+                        size_after,
+                    ));
+                    pass_start = Instant::now();
+                });
+
+        for (name, parameters, duration, size_after) in applied {
+            let size_after = size_after.transpose()?;
+            passes.push(PassReport {
+                name,
+                parameters,
+                duration,
+                sizes: size_before
+                    .zip(size_after)
+                    .map(|(size_before, size_after)| PassSizes {
+                        size_before,
+                        size_after,
+                    }),
+            });
+            *size_before = size_after;
+        }
+
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+    use crate::{CoordinatePrecision, EditorNamespaces, optimize_str, passes};
+
+    const SOURCE: &str = concat!(
+        r#"<?xml version="1.0"?><svg xmlns:a="http://example.com/a" a:b="c">"#,
+        r#"<!-- comment --><g><rect width="1.2345" height="1"/></g></svg>"#
+    );
+
+    #[test]
+    fn test_optimize_str() -> Result<()> {
+        assert_eq!(
+            optimize_str(SOURCE, &Config::default())?,
+            r#"<svg xmlns:a="http://example.com/a" a:b="c"><rect height="1" width="1.2345"/></svg>"#
+        );
+        assert_eq!(
+            optimize_str(
+                SOURCE,
+                &Config::default()
+                    .passes(&["remove_comments", "round_floats"])?
+                    .precision(1)
+                    .keep_declarations()
+            )?,
+            r#"<?xml version="1.0"?><svg xmlns:a="http://example.com/a" a:b="c"><g><rect width="1.2" height="1"/></g></svg>"#
+        );
+        assert_eq!(
+            optimize_str(
+                SOURCE,
+                &Config::default()
+                    .disable_by_default()
+                    .enable("remove_useless_groups")?
+                    .indent(1)
+            )?,
+            "<svg xmlns:a=\"http://example.com/a\" a:b=\"c\">\n <!-- comment -->\n <rect width=\"1.2345\" height=\"1\"/>\n</svg>\n"
+        );
+        assert!(Config::default().passes(&["remove-comments"]).is_err());
+        assert!(Config::default().disable("unknown").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let Err(Error::Parse(error)) = Config::default().parse("<svg>\n  <g></svg>") else {
+            panic!("mismatched tags should not be parsed");
+        };
+        assert_eq!(error.position(), Some((2, 11)));
+        assert!(std::error::Error::source(&error).is_some());
+
+        let Err(Error::Parse(error)) =
+            Config::default().parse(r#"<!DOCTYPE svg [<!ENTITY a SYSTEM "a.txt">]><svg>&a;</svg>"#)
+        else {
+            panic!("external entities should not be parsed");
+        };
+        assert!(matches!(error, ParseError::ExternalEntity(name) if name == "a"));
+    }
+
+    #[test]
+    fn test_parameters() -> Result<()> {
+        let config = Config::default()
+            .preset(Preset::Aggressive)
+            .coordinate_precision(1)
+            .keep_description(DescriptionElement::Title);
+
+        assert_eq!(
+            optimize_str(
+                r#"<svg><title>A</title><desc>B</desc><rect width="1.2345" height="1" transform="rotate(45) scale(2)"/></svg>"#,
+                &config
+            )?,
+            r#"<svg><title>A</title><rect height="1" transform="matrix(1.41 1.41 -1.41 1.41 0 0)" width="1.2"/></svg>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_passes() -> Result<()> {
+        let mut document: Document = SOURCE.parse()?;
+        passes::remove_comments(&mut document);
+        passes::remove_editor_namespace_data(
+            &mut document,
+            &EditorNamespaces::default().remove_namespace("http://example.com/a"),
+        );
+        passes::round_floats(&mut document, &CoordinatePrecision::new(2));
+
+        assert_eq!(
+            document.to_string(),
+            r#"<svg><g><rect width="1.23" height="1"/></g></svg>"#
+        );
+
+        Ok(())
+    }
+
+    struct BrandColors {
+        lossy: bool,
+    }
+
+    impl Optimization for BrandColors {
+        fn name(&self) -> &'static str {
+            if self.lossy {
+                "lossy_brand_colors"
+            } else {
+                "brand_colors"
+            }
+        }
+
+        fn description(&self) -> &'static str {
+            "Replace the brand color with a named color."
+        }
+
+        fn lossy(&self) -> bool {
+            self.lossy
+        }
+
+        fn apply(&self, document: &mut Document) {
+            document.for_each_attribute(|_, name, value| {
+                if name == "fill" && value == "#e11" {
+                    *value = "red".to_string();
+                }
+            });
+        }
+    }
+
+    fn applied_passes(config: &Config) -> Result<Vec<&'static str>> {
+        let nodes = config
+            .parse_nodes(r##"<svg><rect width="1" height="1" fill="#e11"/></svg>"##.as_bytes())?;
+        let (_, passes) = config.apply_optimizations(nodes, false)?;
+        Ok(passes.into_iter().map(|pass| pass.name).collect())
+    }
+
+    #[test]
+    fn test_custom_optimization() -> Result<()> {
+        let config = Config::default()
+            .register(BrandColors { lossy: false })?
+            .register(BrandColors { lossy: true })?;
+        assert_eq!(
+            optimize_str(
+                r##"<svg><rect width="1" height="1" fill="#e11"/></svg>"##,
+                &config
+            )?,
+            r#"<svg><rect fill="red" height="1" width="1"/></svg>"#
+        );
+
+        let passes = applied_passes(&config)?;
+        assert_eq!(passes.last(), Some(&"brand_colors"));
+        assert!(!passes.contains(&"lossy_brand_colors"));
+
+        let aggressive = applied_passes(&config.clone().preset(Preset::Aggressive))?;
+        assert!(aggressive.contains(&"lossy_brand_colors"));
+
+        let disabled = applied_passes(&config.clone().disable("brand_colors")?)?;
+        assert!(!disabled.contains(&"brand_colors"));
+
+        // The second iteration does not shrink the output, so its passes are not reported
+        let ordered = config
+            .clone()
+            .passes(&["brand_colors", "remove_comments", "brand_colors"])?
+            .multipass(NonZeroUsize::new(2).unwrap());
+        assert_eq!(
+            applied_passes(&ordered)?,
+            vec!["brand_colors", "remove_comments", "brand_colors"]
+        );
+
+        assert!(config.register(BrandColors { lossy: false }).is_err());
+
+        Ok(())
+    }
+}
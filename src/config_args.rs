@@ -0,0 +1,458 @@
+use crate::config::Config;
+use crate::config_file::{Options, Value};
+use crate::error::Result;
+use crate::optimizations::{ALL_OPTIMIZATIONS, DescriptionElement, Optimizations, Preset};
+use crate::parser::ParserOptions;
+use crate::writer::WriterOptions;
+use clap::builder::PossibleValuesParser;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Args, Command, FromArgMatches};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+
+/// Set of optimizations enabled by default, along with their parameters.
+#[derive(Clone, Copy, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum PresetArg {
+    /// Optimizations which do not change ids or the structure of the document.
+    Safe,
+    /// All optimizations except the ones which are disabled by default.
+    #[default]
+    Default,
+    /// All optimizations and lossy ones, with precision 2.
+    Aggressive,
+    /// All optimizations and lossy ones, with precision 1 suited for small icons.
+    Icons,
+}
+
+impl From<PresetArg> for Preset {
+    fn from(preset: PresetArg) -> Self {
+        match preset {
+            PresetArg::Safe => Self::Safe,
+            PresetArg::Default => Self::Default,
+            PresetArg::Aggressive => Self::Aggressive,
+            PresetArg::Icons => Self::Icons,
+        }
+    }
+}
+
+/// Element removed by `--remove-descriptions`.
+#[derive(Clone, Copy, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum DescriptionElementArg {
+    /// `<title>`, shown as a tooltip and read by screen readers.
+    Title,
+    /// `<desc>`.
+    Desc,
+    /// `<metadata>`.
+    Metadata,
+}
+
+impl From<DescriptionElementArg> for DescriptionElement {
+    fn from(element: DescriptionElementArg) -> Self {
+        match element {
+            DescriptionElementArg::Title => Self::Title,
+            DescriptionElementArg::Desc => Self::Desc,
+            DescriptionElementArg::Metadata => Self::Metadata,
+        }
+    }
+}
+
+/// Command line flags of optimizations which are not named after the optimization.
+const RENAMED_FLAGS: &[(&str, &str)] = &[("convert_to_svg2", "svg2")];
+
+fn flag_name(name: &str) -> String {
+    RENAMED_FLAGS
+        .iter()
+        .find(|(renamed, _)| *renamed == name)
+        .map_or_else(|| name.replace('_', "-"), |(_, flag)| (*flag).to_string())
+}
+
+fn disable_id(name: &str) -> String {
+    format!("no_{name}")
+}
+
+/// Returns the name of the optimization enabled or disabled by the flag.
+fn optimization_of_flag(flag: &str) -> Option<&'static str> {
+    ALL_OPTIMIZATIONS
+        .iter()
+        .map(|(name, _)| *name)
+        .find(|name| flag_name(name) == flag)
+}
+
+/// Flags which enable or disable single optimizations, `--{name}` and `--no-{name}` with the name in kebab case.
+///
+/// Help of the flags is written like the one of derived arguments, without the final period.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OptimizationFlags {
+    enabled: BTreeMap<&'static str, bool>,
+}
+
+impl OptimizationFlags {
+    #[cfg(test)]
+    /// Returns `Some` if the optimization was explicitly enabled or disabled.
+    pub(crate) fn enabled(&self, name: &str) -> Option<bool> {
+        self.enabled.get(name).copied()
+    }
+}
+
+impl OptimizationFlags {
+    /// Enables or disables the optimizations whose flags are set to `true` or `false` in a configuration file, unless
+    /// their flags were given on the command line.
+    fn merge(&mut self, options: Options, is_given: impl Fn(&str) -> bool) -> anyhow::Result<()> {
+        for (flag, value) in options {
+            let Some(name) = optimization_of_flag(&flag) else {
+                return Err(anyhow::Error::msg(format!("Unknown option `{flag}`")));
+            };
+            let Value::Boolean(enabled) = value else {
+                return Err(anyhow::Error::msg(format!(
+                    "Option `{flag}` has to be `true` or `false`"
+                )));
+            };
+
+            if !is_given(name) && !is_given(&disable_id(name)) {
+                self.enabled.insert(name, enabled);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Args for OptimizationFlags {
+    fn augment_args(command: Command) -> Command {
+        command.args(ALL_OPTIMIZATIONS.iter().flat_map(|(name, description)| {
+            let flag = flag_name(name);
+            [
+                Arg::new(*name)
+                    .long(flag.clone())
+                    .action(ArgAction::SetTrue)
+                    .help(description.trim_end_matches('.')),
+                Arg::new(disable_id(name))
+                    .long(format!("no-{flag}"))
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with(*name)
+                    .help("Disable the optimization"),
+            ]
+        }))
+    }
+
+    fn augment_args_for_update(command: Command) -> Command {
+        Self::augment_args(command)
+    }
+}
+
+impl FromArgMatches for OptimizationFlags {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        let mut flags = Self::default();
+        flags.update_from_arg_matches(matches)?;
+        Ok(flags)
+    }
+
+    // Flags which were not given keep their state, so that a configuration can be applied on top of the command line
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        let is_set = |id: &str| matches.get_one::<bool>(id) == Some(&true);
+
+        for (name, _) in ALL_OPTIMIZATIONS {
+            if is_set(name) {
+                self.enabled.insert(name, true);
+            } else if is_set(&disable_id(name)) {
+                self.enabled.insert(name, false);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Command line options converted to a [`Config`].
+#[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct ConfigArgs {
+    /// Keep the xml declaration, e.g. `<?xml version="1.0" encoding="UTF-8"?>`, if present.
+    #[arg(long)]
+    keep_xml_declaration: bool,
+
+    /// Keep the DOCTYPE declaration, including its internal subset, if present.
+    #[arg(long)]
+    keep_doctype: bool,
+
+    /// Remove entity declarations from the kept DOCTYPE. Entity references are always replaced with their values.
+    #[arg(long, requires = "keep_doctype")]
+    inline_entities: bool,
+
+    /// Write each element on a separate line, indented according to its depth.
+    /// Whitespace is left as is where it may be significant, e.g. in `<text>` or with `xml:space="preserve"`.
+    #[arg(long)]
+    pretty: bool,
+
+    /// Number of spaces used for a single level of indentation in pretty output. Implies `--pretty`.
+    #[arg(long, value_name = "N")]
+    pub(crate) indent: Option<usize>,
+
+    /// Disable all optimizations by default.
+    #[arg(short, long)]
+    disable_by_default: bool,
+
+    /// Apply the optimizations again as long as the output shrinks, at most N times in total (10 by default).
+    /// N has to be given after `=`, e.g. `--multipass=3`, as a separate argument is read as an input file.
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10"
+    )]
+    pub(crate) multipass: Option<NonZeroUsize>,
+
+    #[command(flatten)]
+    pub(crate) flags: OptimizationFlags,
+
+    /// Precision of numbers rounded by `--round-floats`, instead of the one of `--precision`.
+    #[arg(long, value_name = "DIGITS")]
+    coordinate_precision: Option<usize>,
+
+    /// Precision of matrices created by `--merge-transforms`, instead of the one of `--precision`.
+    #[arg(long, value_name = "DIGITS")]
+    transform_precision: Option<usize>,
+
+    /// Desired precision for lossy optimizations without their own precision given. Defaults to 3, or the precision
+    /// of the preset.
+    #[arg(short, long)]
+    precision: Option<usize>,
+
+    /// Set of optimizations enabled by default, and their parameters. Each optimization can still be enabled or
+    /// disabled with its flag, and `-d` disables the optimizations of the preset.
+    #[arg(long, value_enum, default_value_t)]
+    preset: PresetArg,
+
+    /// Apply exactly the given optimizations in the given order, possibly repeated, e.g.
+    /// `remove_comments,shorten_ids`. Names are listed by `--list-passes`. Flags of single optimizations, `-d` and
+    /// the preset are then ignored.
+    #[arg(
+        long,
+        value_name = "NAMES",
+        value_delimiter = ',',
+        hide_possible_values = true,
+        value_parser = PossibleValuesParser::new(ALL_OPTIMIZATIONS.iter().map(|(name, _)| *name))
+    )]
+    passes: Vec<String>,
+
+    /// Element which should be kept by `--remove-descriptions`. Can be given multiple times.
+    #[arg(long, value_enum, value_name = "ELEMENT")]
+    keep_description: Vec<DescriptionElementArg>,
+
+    /// Minimum estimated number of bytes saved by `--convert-paths-to-uses` for each set of identical paths.
+    /// By default, all identical paths are converted.
+    #[arg(long, value_name = "BYTES")]
+    min_use_saving: Option<usize>,
+
+    /// URI of a namespace which should be kept, even if it belongs to a known editor. Can be given multiple times.
+    #[arg(long)]
+    keep_namespace: Vec<String>,
+
+    /// URI of an additional namespace which should be removed. Can be given multiple times.
+    #[arg(long)]
+    remove_namespace: Vec<String>,
+}
+
+/// Options of [`ConfigArgs`] read from a configuration file, with the names of the command line options as keys.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ConfigFileArgs {
+    keep_xml_declaration: Option<bool>,
+    keep_doctype: Option<bool>,
+    inline_entities: Option<bool>,
+    pretty: Option<bool>,
+    indent: Option<usize>,
+    disable_by_default: Option<bool>,
+    multipass: Option<NonZeroUsize>,
+    coordinate_precision: Option<usize>,
+    transform_precision: Option<usize>,
+    precision: Option<usize>,
+    preset: Option<PresetArg>,
+    passes: Option<Vec<String>>,
+    keep_description: Option<Vec<DescriptionElementArg>>,
+    min_use_saving: Option<usize>,
+    keep_namespace: Option<Vec<String>>,
+    remove_namespace: Option<Vec<String>>,
+    /// Flags of single optimizations, along with any unknown keys.
+    #[serde(flatten)]
+    flags: Options,
+}
+
+impl ConfigFileArgs {
+    /// Returns keys which are neither options nor flags of single optimizations.
+    pub(crate) fn unknown_keys(&self) -> impl Iterator<Item = &str> {
+        self.flags
+            .keys()
+            .map(String::as_str)
+            .filter(|key| optimization_of_flag(key).is_none())
+    }
+}
+
+impl ConfigArgs {
+    /// Replaces options with the ones read from a configuration file, except the ones given on the command line.
+    pub(crate) fn merge(
+        &mut self,
+        options: ConfigFileArgs,
+        matches: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        let is_given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(value) = options.$field
+                        && !is_given(stringify!($field))
+                    {
+                        self.$field = value;
+                    }
+                )*
+            };
+            (Some: $($field:ident),*) => {
+                $(
+                    if let Some(value) = options.$field
+                        && !is_given(stringify!($field))
+                    {
+                        self.$field = Some(value);
+                    }
+                )*
+            };
+        }
+
+        merge!(
+            keep_xml_declaration,
+            keep_doctype,
+            inline_entities,
+            pretty,
+            disable_by_default,
+            preset,
+            passes,
+            keep_description,
+            keep_namespace,
+            remove_namespace
+        );
+        merge!(Some: indent, multipass, coordinate_precision, transform_precision, precision, min_use_saving);
+
+        self.flags.merge(options.flags, is_given)
+    }
+
+    pub(crate) fn config(&self) -> Result<Config> {
+        let mut config = Config {
+            parser_options: ParserOptions {
+                keep_xml_declaration: self.keep_xml_declaration,
+                keep_doctype: self.keep_doctype,
+                inline_entities: self.inline_entities,
+            },
+            writer_options: WriterOptions {
+                pretty: self.pretty,
+                indent: self.indent,
+            },
+            disable_by_default: self.disable_by_default,
+            multipass: self.multipass,
+            optimizations: Optimizations::default(),
+        }
+        .preset(self.preset.into());
+
+        for &element in &self.keep_description {
+            config = config.keep_description(element.into());
+        }
+        if let Some(bytes) = self.min_use_saving {
+            config = config.min_use_saving(bytes);
+        }
+        for uri in &self.keep_namespace {
+            config = config.keep_namespace(uri);
+        }
+        for uri in &self.remove_namespace {
+            config = config.remove_namespace(uri);
+        }
+        if let Some(precision) = self.precision {
+            config = config.precision(precision);
+        }
+        if let Some(precision) = self.coordinate_precision {
+            config = config.coordinate_precision(precision);
+        }
+        if let Some(precision) = self.transform_precision {
+            config = config.transform_precision(precision);
+        }
+        if !self.passes.is_empty() {
+            let passes: Vec<&str> = self.passes.iter().map(String::as_str).collect();
+            config = config.passes(&passes)?;
+        }
+        for (name, &enabled) in &self.flags.enabled {
+            config = if enabled {
+                config.enable(name)?
+            } else {
+                config.disable(name)?
+            };
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(clap::Parser)]
+    struct TestArgs {
+        #[command(flatten)]
+        config: ConfigArgs,
+    }
+
+    fn config(args: &[&str]) -> anyhow::Result<Config> {
+        let args = TestArgs::try_parse_from([&["main.exe"], args].concat())?;
+        Ok(args.config.config()?)
+    }
+
+    #[test]
+    fn test_config() -> anyhow::Result<()> {
+        assert_eq!(config(&[])?, Config::default());
+        assert_eq!(
+            config(&[
+                "--preset",
+                "safe",
+                "--shorten-ids",
+                "--no-remove-comments",
+                "--no-svg2",
+                "--keep-description",
+                "title",
+                "--min-use-saving",
+                "20",
+                "--remove-namespace",
+                "a",
+                "--precision",
+                "2",
+                "--indent",
+                "1",
+                "--keep-xml-declaration",
+                "--keep-doctype",
+            ])?,
+            Config::default()
+                .preset(Preset::Safe)
+                .enable("shorten_ids")?
+                .disable("remove_comments")?
+                .disable("convert_to_svg2")?
+                .keep_description(DescriptionElement::Title)
+                .min_use_saving(20)
+                .remove_namespace("a")
+                .precision(2)
+                .indent(1)
+                .keep_declarations()
+        );
+        assert_eq!(
+            config(&["--passes", "shorten_ids,remove_comments"])?,
+            Config::default().passes(&["shorten_ids", "remove_comments"])?
+        );
+
+        assert!(config(&["--passes", "shorten-ids"]).is_err());
+        assert!(config(&["--shorten-ids", "--no-shorten-ids"]).is_err());
+        assert!(config(&["--inline-entities"]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::walker::Glob;
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const CONFIG_FILE_NAME: &str = "svg-optimizer.toml";
+
+/// Options by their keys, with `no-{name} = true` read as `{name} = false`.
+pub(crate) type Options = toml::Table;
+
+/// Value of an option in a configuration file.
+pub(crate) type Value = toml::Value;
+
+/// A single pattern or a list of patterns.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Patterns {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct OverrideSource {
+    files: Patterns,
+    #[serde(flatten)]
+    options: Options,
+}
+
+#[derive(Deserialize)]
+struct ConfigSource {
+    #[serde(default)]
+    overrides: Vec<OverrideSource>,
+    #[serde(flatten)]
+    options: Options,
+}
+
+/// Options which apply to files matching any of the patterns, instead of options given earlier.
+struct Override {
+    files: Vec<Glob>,
+    options: Options,
+}
+
+/// Options read from a TOML configuration file.
+///
+/// Keys are names of command line options without leading dashes. `no-{name} = true` is read as `{name} = false`.
+/// Sections `[[overrides]]` with a `files` pattern or list of patterns, matched against paths relative to the
+/// configuration file, override options for matching files.
+pub(crate) struct ConfigFile {
+    dir: PathBuf,
+    options: Options,
+    overrides: Vec<Override>,
+}
+
+fn normalize_options(options: Options) -> Options {
+    options
+        .into_iter()
+        .map(|(key, value)| match (key.strip_prefix("no-"), value) {
+            (Some(key), Value::Boolean(value)) => (key.to_string(), Value::Boolean(!value)),
+            (_, value) => (key, value),
+        })
+        .collect()
+}
+
+impl Patterns {
+    fn parse(self) -> Result<Vec<Glob>> {
+        match self {
+            Patterns::One(pattern) => Ok(vec![pattern.parse()?]),
+            Patterns::Many(patterns) => patterns.iter().map(|pattern| pattern.parse()).collect(),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Returns the path of the closest configuration file in the directory or its ancestors.
+    pub(crate) fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path)?;
+        let dir = std::path::absolute(path)?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        Self::parse(&source, dir)
+            .map_err(|error| Error::msg(format!("{}: {error}", path.display())))
+    }
+
+    fn parse(source: &str, dir: PathBuf) -> Result<Self> {
+        let source: ConfigSource = toml::from_str(source)?;
+        let overrides = source
+            .overrides
+            .into_iter()
+            .map(|config_override| {
+                Ok(Override {
+                    files: config_override.files.parse()?,
+                    options: normalize_options(config_override.options),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            dir,
+            options: normalize_options(source.options),
+            overrides,
+        })
+    }
+
+    /// Returns indices of the overrides which apply to the file.
+    ///
+    /// Overrides never apply to standard input.
+    pub(crate) fn matching_overrides(&self, path: Option<&Path>) -> Result<Vec<usize>> {
+        let relative_path = match path {
+            Some(path) => std::path::absolute(path)?
+                .strip_prefix(&self.dir)
+                .ok()
+                .map(Path::to_path_buf),
+            None => None,
+        };
+
+        Ok(self
+            .overrides
+            .iter()
+            .enumerate()
+            .filter(|(_, config_override)| {
+                relative_path.as_ref().is_some_and(|path| {
+                    config_override.files.iter().any(|glob| glob.is_match(path))
+                })
+            })
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    /// Returns options with the overrides applied in order.
+    pub(crate) fn options(&self, overrides: &[usize]) -> Options {
+        let mut options = self.options.clone();
+        for &index in overrides {
+            options.extend(self.overrides[index].options.clone());
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options_for(config: &ConfigFile, path: Option<&str>) -> Result<Options> {
+        let overrides = config.matching_overrides(path.map(Path::new))?;
+        Ok(config.options(&overrides))
+    }
+
+    #[test]
+    fn test_parse_config() -> Result<()> {
+        let config = ConfigFile::parse(
+            r#"
+            # Comment
+            precision = 2 # Trailing comment
+            round-floats = true
+            no-shorten-ids = true
+            remove-namespace = [
+                "http://example.com/a",
+                'http://example.com/#b',
+            ]
+
+            [[overrides]]
+            files = "icons/**"
+            precision = 1_0
+            indent = 4
+
+            [[overrides]]
+            files = ["icons/large/*.svg", "logo.svg"]
+            shorten-ids = true
+            "#,
+            PathBuf::from("/project"),
+        )?;
+
+        let expected: Options = toml::from_str(
+            r#"
+            precision = 2
+            round-floats = true
+            shorten-ids = false
+            remove-namespace = ["http://example.com/a", "http://example.com/#b"]
+            "#,
+        )?;
+        assert_eq!(options_for(&config, Some("/project/a.svg"))?, expected);
+
+        let expected: Options = toml::from_str(
+            r#"
+            precision = 10
+            indent = 4
+            round-floats = true
+            shorten-ids = true
+            remove-namespace = ["http://example.com/a", "http://example.com/#b"]
+            "#,
+        )?;
+        assert_eq!(
+            options_for(&config, Some("/project/icons/large/b.svg"))?,
+            expected
+        );
+        assert_eq!(options_for(&config, None)?.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_inline_tables() -> Result<()> {
+        let config = ConfigFile::parse(
+            r#"
+            pretty = true
+            overrides = [{ files = "logo.svg", pretty = false }]
+            "#,
+            PathBuf::from("/project"),
+        )?;
+
+        assert_eq!(
+            options_for(&config, Some("/project/logo.svg"))?.get("pretty"),
+            Some(&Value::Boolean(false))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        let dir = PathBuf::from("/project");
+
+        assert!(ConfigFile::parse("precision = ", dir.clone()).is_err());
+        assert!(ConfigFile::parse("precision = 2 3", dir.clone()).is_err());
+        assert!(ConfigFile::parse("description = \"unclosed", dir.clone()).is_err());
+        assert!(ConfigFile::parse("[[overrides]]\nprecision = 2", dir.clone()).is_err());
+        assert!(ConfigFile::parse("[[overrides]]\nfiles = \"[a\"", dir).is_err());
+    }
+}
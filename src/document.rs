@@ -0,0 +1,310 @@
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::node::{Node, NodeNamespace, RegularNodeType};
+use crate::writer::SVGWriter;
+use std::fmt;
+use std::str::FromStr;
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::namespace::NS_NO_PREFIX;
+
+/// Parsed SVG document, which can be optimized with a [`Config`] or single [`passes`](crate::passes).
+///
+/// Parsing with [`str::parse`] uses the default configuration, and formatting writes the document without
+/// indentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    pub(crate) nodes: Vec<Node>,
+}
+
+/// Element of a [`Document`], whose name, attributes and children custom [`Optimization`](crate::Optimization)s can
+/// change.
+///
+/// Names of elements and attributes are written as in the source, including their prefix, e.g. `xlink:href`. Prefixes
+/// of new names have to be declared on the element or its ancestors.
+pub struct Element<'a> {
+    node_type: &'a mut RegularNodeType,
+    namespace: &'a mut NodeNamespace,
+    attributes: &'a mut Vec<OwnedAttribute>,
+    children: &'a mut Vec<Node>,
+}
+
+impl<'a> Element<'a> {
+    fn new(node: &'a mut Node) -> Option<Self> {
+        match node {
+            Node::RegularNode {
+                node_type,
+                namespace,
+                attributes,
+                children,
+            } => Some(Self {
+                node_type,
+                namespace,
+                attributes,
+                children,
+            }),
+            Node::ChildlessNode { .. } => None,
+        }
+    }
+
+    /// Parses the name and resolves its prefix in the namespaces in scope of the element. Names without a prefix are
+    /// in the default namespace if `default_namespace` is set, and in no namespace otherwise.
+    fn resolve_name(&self, name: &str, default_namespace: bool) -> Result<OwnedName> {
+        let mut name = name.parse().unwrap_or_else(|()| OwnedName::local(name));
+        let namespaces = &self.namespace.element_namespace;
+
+        name.namespace = match &name.prefix {
+            Some(prefix) => Some(
+                namespaces
+                    .get(prefix)
+                    .ok_or_else(|| Error::UnboundPrefix(prefix.clone()))?
+                    .to_string(),
+            ),
+            None if default_namespace => namespaces.get(NS_NO_PREFIX).map(ToString::to_string),
+            None => None,
+        };
+        Ok(name)
+    }
+
+    /// Returns the name of the element, e.g. `rect` or `inkscape:grid`.
+    #[must_use]
+    pub fn name(&self) -> String {
+        match &self.namespace.prefix {
+            Some(prefix) => format!("{prefix}:{}", self.node_type),
+            None => self.node_type.to_string(),
+        }
+    }
+
+    /// Replaces the element with an element of another name, keeping its attributes and children.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prefix of the name is not declared.
+    pub fn rename(&mut self, name: &str) -> Result<()> {
+        let name = self.resolve_name(name, true)?;
+        *self.node_type = RegularNodeType::from(name.local_name);
+        self.namespace.prefix = name.prefix;
+        self.namespace.parent_namespace = name.namespace;
+        Ok(())
+    }
+
+    /// Returns the value of the attribute, if present.
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.borrow().to_repr() == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Sets the value of the attribute, adding it after the other attributes if it is not present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the attribute is not present and the prefix of its name is not declared.
+    pub fn set_attribute(&mut self, name: &str, value: impl Into<String>) -> Result<()> {
+        let value = value.into();
+        if let Some(attribute) = self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.name.borrow().to_repr() == name)
+        {
+            attribute.value = value;
+        } else {
+            let name = self.resolve_name(name, false)?;
+            self.attributes.push(OwnedAttribute::new(name, value));
+        }
+        Ok(())
+    }
+
+    /// Removes the attribute, returning its value if it was present.
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        let index = self
+            .attributes
+            .iter()
+            .position(|attribute| attribute.name.borrow().to_repr() == name)?;
+
+        Some(self.attributes.remove(index).value)
+    }
+
+    /// Adds an empty element as the last child. Names without a prefix are in the default namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prefix of the name is not declared.
+    pub fn append_child(&mut self, name: &str) -> Result<Element<'_>> {
+        let name = self.resolve_name(name, true)?;
+        self.children.push(Node::RegularNode {
+            node_type: RegularNodeType::from(name.local_name),
+            namespace: NodeNamespace {
+                parent_namespace: name.namespace,
+                prefix: name.prefix,
+                element_namespace: self.namespace.element_namespace.clone(),
+            },
+            attributes: vec![],
+            children: vec![],
+        });
+
+        self.children
+            .last_mut()
+            .and_then(Element::new)
+            .ok_or_else(|| unreachable!("the child is an element"))
+    }
+}
+
+fn visit_elements(nodes: &mut [Node], visit: &mut impl FnMut(&mut Element<'_>)) {
+    for node in nodes {
+        if let Some(mut element) = Element::new(node) {
+            visit(&mut element);
+            visit_elements(element.children, visit);
+        }
+    }
+}
+
+fn retain_elements(nodes: &mut Vec<Node>, keep: &mut impl FnMut(&Element<'_>) -> bool) {
+    nodes.retain_mut(|node| Element::new(node).is_none_or(|element| keep(&element)));
+
+    for node in nodes {
+        if let Some(element) = Element::new(node) {
+            retain_elements(element.children, keep);
+        }
+    }
+}
+
+impl Document {
+    /// Calls `visit` with each element in the document, before its children, allowing custom
+    /// [`Optimization`](crate::Optimization)s to change the elements and add new children, which are visited as well.
+    pub fn for_each_element(&mut self, mut visit: impl FnMut(&mut Element<'_>)) {
+        visit_elements(&mut self.nodes, &mut visit);
+    }
+
+    /// Removes the elements, along with their children, for which `keep` returns `false`. Children of removed elements
+    /// are not passed to `keep`.
+    pub fn retain_elements(&mut self, mut keep: impl FnMut(&Element<'_>) -> bool) {
+        retain_elements(&mut self.nodes, &mut keep);
+    }
+
+    /// Calls `visit` with the element name, attribute name and value of each attribute in the document, allowing
+    /// custom [`Optimization`](crate::Optimization)s to change the values.
+    pub fn for_each_attribute(&mut self, mut visit: impl FnMut(&str, &str, &mut String)) {
+        self.for_each_element(|element| {
+            let element_name = element.name();
+            for attribute in element.attributes.iter_mut() {
+                visit(
+                    &element_name,
+                    &attribute.name.borrow().to_repr(),
+                    &mut attribute.value,
+                );
+            }
+        });
+    }
+
+    pub(crate) fn map_nodes(&mut self, optimization: impl FnOnce(Vec<Node>) -> Vec<Node>) {
+        self.nodes = optimization(std::mem::take(&mut self.nodes));
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        Config::default().parse(source)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut output = Vec::new();
+        SVGWriter::new(&mut output)
+            .write(&self.nodes)
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(&String::from_utf8_lossy(&output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(element: &mut Element<'_>) -> Result<()> {
+        match element.name().as_str() {
+            "g" => {
+                element.remove_attribute("id");
+                element.set_attribute("fill", "red")?;
+                element.append_child("circle")?.set_attribute("r", "1")?;
+            }
+            "rect" => {
+                element.rename("ellipse")?;
+                element.set_attribute("width", "2")?;
+            }
+            "circle" => element.set_attribute("cx", "1")?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_elements() -> Result<()> {
+        let mut document: Document =
+            r#"<svg><g id="a"><rect width="1"/><script/></g><text>t</text></svg>"#.parse()?;
+
+        document.for_each_element(|element| edit(element).expect("the names are valid"));
+        document.retain_elements(|element| element.name() != "script");
+
+        assert_eq!(
+            document.to_string(),
+            r#"<svg><g fill="red"><ellipse width="2"/><circle r="1" cx="1"/></g><text>t</text></svg>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefixed_names() -> Result<()> {
+        let mut document: Document = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" "#,
+            r#"xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">"#,
+            r#"<inkscape:grid/><use/></svg>"#
+        )
+        .parse()?;
+
+        let mut names = vec![];
+        let mut errors = vec![];
+        document.for_each_element(|element| {
+            names.push(element.name());
+            if element.name() == "use" {
+                element
+                    .set_attribute("xlink:href", "#a")
+                    .expect("xlink is declared");
+                assert_eq!(element.attribute("xlink:href"), Some("#a"));
+                errors.push(
+                    element
+                        .set_attribute("sodipodi:role", "line")
+                        .map_err(|error| error.to_string()),
+                );
+                element
+                    .append_child("inkscape:path")
+                    .expect("inkscape is declared");
+            }
+        });
+
+        assert_eq!(names, ["svg", "inkscape:grid", "use", "inkscape:path"]);
+        assert_eq!(
+            errors,
+            [Err(
+                "Namespace prefix `sodipodi` is not declared".to_string()
+            )]
+        );
+        assert_eq!(
+            document.to_string(),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" "#,
+                r#"xmlns:xlink="http://www.w3.org/1999/xlink">"#,
+                r##"<inkscape:grid/><use xlink:href="#a"><inkscape:path/></use></svg>"##
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::io;
+use xml::common::Position;
+
+/// Error returned by the library.
+#[derive(Debug)]
+pub enum Error {
+    /// The source is not a valid SVG document, or uses features which are not supported, e.g. external entities.
+    Parse(ParseError),
+    /// The document could not be written.
+    Write(io::Error),
+    /// There is no optimization with the name.
+    UnknownOptimization(String),
+    /// An optimization with the name is already registered.
+    DuplicateOptimization(String),
+    /// A name of a new element or attribute has a prefix which is not declared.
+    UnboundPrefix(String),
+}
+
+/// Result of the library functions.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error returned when a source cannot be parsed.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The source could not be read.
+    Read(io::Error),
+    /// The source is not well-formed XML.
+    Xml(xml::reader::Error),
+    /// The DOCTYPE declares an external entity with the name, which is not supported.
+    ExternalEntity(String),
+}
+
+impl ParseError {
+    /// Returns the line and column, starting at 1, where the source is not well-formed.
+    #[must_use]
+    pub fn position(&self) -> Option<(u64, u64)> {
+        match self {
+            Self::Xml(error) => {
+                let position = error.position();
+                Some((position.row + 1, position.column + 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(error) => write!(f, "Could not read the document: {error}"),
+            Self::Xml(error) => write!(f, "{error}"),
+            Self::ExternalEntity(name) => write!(f, "External entity `{name}` is not supported"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read(error) => Some(error),
+            Self::Xml(error) => Some(error),
+            Self::ExternalEntity(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        Self::Read(error)
+    }
+}
+
+impl From<xml::reader::Error> for ParseError {
+    fn from(error: xml::reader::Error) -> Self {
+        Self::Xml(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{error}"),
+            Self::Write(error) => write!(f, "Could not write the document: {error}"),
+            Self::UnknownOptimization(name) => write!(f, "Unknown optimization `{name}`"),
+            Self::DuplicateOptimization(name) => {
+                write!(f, "Optimization `{name}` is already registered")
+            }
+            Self::UnboundPrefix(prefix) => write!(f, "Namespace prefix `{prefix}` is not declared"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            Self::Write(error) => Some(error),
+            _ => None,
+        }
+    }
+}
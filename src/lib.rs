@@ -0,0 +1,49 @@
+//! Optimizer of SVG files, usable as a library as well as through the `svg-optimizer` command line program.
+//!
+//! ```
+//! use svg_optimizer::{Config, Preset, optimize_str};
+//!
+//! let config = Config::default().preset(Preset::Aggressive);
+//! let optimized = optimize_str(r#"<svg><!-- comment --><g><rect width="1.2345" height="1"/></g></svg>"#, &config)?;
+//! assert_eq!(optimized, r#"<svg><rect height="1" width="1.23"/></svg>"#);
+//! # Ok::<(), svg_optimizer::Error>(())
+//! ```
+
+mod config;
+mod config_args;
+mod config_file;
+mod document;
+mod error;
+mod linter;
+mod node;
+mod optimizations;
+mod optimizer;
+mod parser;
+mod report;
+mod walker;
+mod writer;
+
+pub use config::Config;
+pub use document::{Document, Element};
+pub use error::{Error, ParseError, Result};
+pub use optimizations::{
+    CoordinatePrecision, DescriptionElement, EditorNamespaces, KeptDescriptions, Optimization,
+    Parameter, ParameterValue, PathUses, Preset, TransformPrecision, passes,
+};
+
+/// Parses the source, applies the optimizations enabled in the config and writes the result.
+///
+/// # Errors
+///
+/// Returns an error if the source is not a valid SVG document.
+pub fn optimize_str(source: &str, config: &Config) -> Result<String> {
+    let mut document = config.parse(source)?;
+    config.optimize(&mut document)?;
+    config.write(&document)
+}
+
+/// Runs the command line program with the arguments of the current process.
+#[doc(hidden)]
+pub fn run() -> anyhow::Result<()> {
+    optimizer::Optimizer::run(std::env::args_os())
+}
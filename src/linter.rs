@@ -0,0 +1,198 @@
+use crate::node::Node;
+use crate::optimizations::common::{
+    id_generator::is_hex_color_prefix,
+    id_usage::{IdUsage, ReferenceKind},
+};
+use std::fmt;
+
+/// Problem with ids found in a document, which optimizations may make worse.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum LintFinding {
+    BrokenReference {
+        id: String,
+        element_path: String,
+    },
+    DuplicateId {
+        id: String,
+        element_paths: Vec<String>,
+    },
+    UnshortenableId {
+        id: String,
+        element_path: String,
+    },
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BrokenReference { id, element_path } => {
+                write!(f, "{element_path}: reference to non-existent id `{id}`")
+            }
+            Self::DuplicateId { id, element_paths } => {
+                write!(f, "{}: duplicate id `{id}`", element_paths.join(", "))
+            }
+            Self::UnshortenableId { id, element_path } => {
+                write!(
+                    f,
+                    "{element_path}: id `{id}` resembles a hex color and will not be shortened"
+                )
+            }
+        }
+    }
+}
+
+fn element_name(node: &Node) -> Option<String> {
+    match node {
+        Node::RegularNode {
+            node_type,
+            namespace,
+            ..
+        } => Some(match &namespace.prefix {
+            Some(prefix) => format!("{prefix}:{node_type}"),
+            None => node_type.to_string(),
+        }),
+        Node::ChildlessNode { .. } => None,
+    }
+}
+
+/// Converts a node location into a path of element names, e.g. `/svg/g[2]/use`.
+///
+/// Sibling index is only given if there are multiple siblings with the same name.
+fn element_path(nodes: &[Node], location: &[usize]) -> String {
+    let mut segments = vec![];
+    let mut siblings = nodes;
+
+    for &index in location {
+        let node = &siblings[index];
+        let name = element_name(node).unwrap_or_default();
+        let has_same_name = |sibling: &&Node| element_name(sibling).as_ref() == Some(&name);
+
+        if siblings.iter().filter(has_same_name).count() > 1 {
+            let position = siblings[..index].iter().filter(has_same_name).count() + 1;
+            segments.push(format!("{name}[{position}]"));
+        } else {
+            segments.push(name);
+        }
+
+        if let Node::RegularNode { children, .. } = node {
+            siblings = children;
+        }
+    }
+
+    format!("/{}", segments.join("/"))
+}
+
+fn is_broken_reference(id_usage: &IdUsage, id: &str, kind: ReferenceKind) -> bool {
+    !id_usage.is_defined(id)
+        && match kind {
+            ReferenceKind::Href | ReferenceKind::Url | ReferenceKind::Animation => true,
+            // Selectors for missing ids match nothing, and `#id` may as well be a hex color
+            ReferenceKind::Stylesheet => false,
+        }
+}
+
+pub(crate) fn lint(nodes: &[Node]) -> Vec<LintFinding> {
+    let id_usage = IdUsage::new(nodes);
+    let mut findings = vec![];
+
+    for (id, reference) in id_usage.references() {
+        if is_broken_reference(&id_usage, id, reference.kind) {
+            findings.push(LintFinding::BrokenReference {
+                id: id.clone(),
+                element_path: element_path(nodes, &reference.location),
+            });
+        }
+    }
+
+    for (id, locations) in id_usage.definitions() {
+        if locations.len() > 1 {
+            findings.push(LintFinding::DuplicateId {
+                id: id.clone(),
+                element_paths: locations
+                    .iter()
+                    .map(|location| element_path(nodes, location))
+                    .collect(),
+            });
+        }
+
+        if is_hex_color_prefix(id) {
+            findings.extend(
+                locations
+                    .iter()
+                    .map(|location| LintFinding::UnshortenableId {
+                        id: id.clone(),
+                        element_path: element_path(nodes, location),
+                    }),
+            );
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_lint() -> anyhow::Result<()> {
+        let test_string = r##"<svg xmlns="http://www.w3.org/2000/svg">
+            <g><rect id="rect" fill="url(#missingGradient)"/></g>
+            <g><rect id="rect"/><use href="#missing"/><use href="#rect"/></g>
+            <circle id="cafe" r="5"><set attributeName="r" to="6" begin="missingAnimation.end"/></circle>
+            <style>#gone { fill: #fff; stroke: url( #missingPattern ); }</style>
+            </svg>"##;
+        let nodes = Parser::new(test_string.as_bytes())?.parse_document()?;
+
+        let findings = lint(&nodes);
+
+        assert_eq!(
+            findings,
+            vec![
+                LintFinding::BrokenReference {
+                    id: "missing".into(),
+                    element_path: "/svg/g[2]/use[1]".into(),
+                },
+                LintFinding::BrokenReference {
+                    id: "missingAnimation".into(),
+                    element_path: "/svg/circle/set".into(),
+                },
+                LintFinding::BrokenReference {
+                    id: "missingGradient".into(),
+                    element_path: "/svg/g[1]/rect".into(),
+                },
+                LintFinding::BrokenReference {
+                    id: "missingPattern".into(),
+                    element_path: "/svg/style".into(),
+                },
+                LintFinding::UnshortenableId {
+                    id: "cafe".into(),
+                    element_path: "/svg/circle".into(),
+                },
+                LintFinding::DuplicateId {
+                    id: "rect".into(),
+                    element_paths: vec!["/svg/g[1]/rect".into(), "/svg/g[2]/rect".into()],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_no_findings() -> anyhow::Result<()> {
+        let test_string = r##"<svg xmlns="http://www.w3.org/2000/svg">
+            <style>
+                #rect { fill: #000066; stroke: #f008; } #missing { fill: #ff000080; color: #abc; }
+                rect { fill: url(#rect) }
+            </style>
+            <rect id="rect"/><use href="#rect"/>
+            </svg>"##;
+        let nodes = Parser::new(test_string.as_bytes())?.parse_document()?;
+
+        assert!(lint(&nodes).is_empty());
+
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
 use std::fmt;
 use xml::attribute::OwnedAttribute;
+use xml::common::XmlVersion;
 use xml::name::OwnedName;
 use xml::namespace::Namespace;
-use xml::reader::XmlEvent;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub(crate) struct NodeNamespace {
@@ -34,28 +34,6 @@ pub(crate) enum Node {
     },
 }
 
-impl RegularNodeType {
-    fn tags(
-        self,
-        namespace: NodeNamespace,
-        attributes: Vec<OwnedAttribute>,
-    ) -> (XmlEvent, XmlEvent) {
-        let name = OwnedName {
-            local_name: self.to_string(),
-            namespace: namespace.parent_namespace,
-            prefix: namespace.prefix,
-        };
-        (
-            XmlEvent::StartElement {
-                name: name.clone(),
-                attributes,
-                namespace: namespace.element_namespace,
-            },
-            XmlEvent::EndElement { name },
-        )
-    }
-}
-
 macro_rules! conversions {
     ($([$node_type:ident, $name:literal]),*) => {
 
@@ -169,99 +147,10 @@ conversions!(
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub(crate) enum ChildlessNodeType {
+    /// Version, whether the encoding is declared and standalone status.
+    XmlDeclaration(XmlVersion, bool, Option<bool>),
+    Doctype(String),
     ProcessingInstruction(String, Option<String>),
     Comment(String),
     Text(String, bool),
 }
-
-impl ChildlessNodeType {
-    fn tag(self) -> XmlEvent {
-        match self {
-            Self::ProcessingInstruction(name, data) => {
-                XmlEvent::ProcessingInstruction { name, data }
-            }
-            Self::Comment(text) => XmlEvent::Comment(text),
-            Self::Text(text, is_cdata) => {
-                if is_cdata {
-                    XmlEvent::CData(text)
-                } else {
-                    XmlEvent::Characters(text)
-                }
-            }
-        }
-    }
-}
-
-impl IntoIterator for Node {
-    type Item = XmlEvent;
-    type IntoIter = NodeIter;
-    fn into_iter(self) -> NodeIter {
-        match self {
-            Node::RegularNode {
-                node_type,
-                namespace,
-                attributes,
-                children,
-            } => {
-                let (start_tag, end_tag) = node_type.tags(namespace, attributes);
-                NodeIter::RegularNodeIter {
-                    start_tag: Some(start_tag),
-                    end_tag: Some(end_tag),
-                    child_iter: None,
-                    children_iter: children.into_iter(),
-                }
-            }
-            Node::ChildlessNode { node_type } => NodeIter::ChildlessNodeIter {
-                tag: Some(node_type.tag()),
-            },
-        }
-    }
-}
-
-pub(crate) enum NodeIter {
-    RegularNodeIter {
-        start_tag: Option<XmlEvent>,
-        end_tag: Option<XmlEvent>,
-        child_iter: Option<Box<NodeIter>>,
-        children_iter: <Vec<Node> as IntoIterator>::IntoIter,
-    },
-    ChildlessNodeIter {
-        tag: Option<XmlEvent>,
-    },
-}
-
-impl Iterator for NodeIter {
-    type Item = XmlEvent;
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            Self::RegularNodeIter {
-                start_tag,
-                end_tag,
-                child_iter,
-                children_iter,
-            } => {
-                if let Some(start_tag) = start_tag.take() {
-                    // starting iteration
-                    Some(start_tag)
-                } else if let Some(iter) = child_iter {
-                    // currently iterating over a child
-                    if let Some(element) = iter.next() {
-                        Some(element)
-                    } else {
-                        // switch to next child
-                        *child_iter = None;
-                        self.next()
-                    }
-                } else if let Some(child) = children_iter.next() {
-                    // not iterating over a child, take next child
-                    *child_iter = Some(Box::new(child.into_iter()));
-                    self.next()
-                } else {
-                    // ran out of children, end
-                    end_tag.take()
-                }
-            }
-            Self::ChildlessNodeIter { tag } => tag.take(),
-        }
-    }
-}
@@ -1,183 +1,392 @@
+use crate::document::Document;
+use crate::error::{Error, Result};
 use crate::node::Node;
+use registry::{Builtin, Kind, PassParameters, Registered};
+use std::sync::Arc;
 
 pub(crate) mod common;
+mod preset;
+mod registry;
+
+pub use preset::Preset;
+pub use registry::{Optimization, Parameter, ParameterValue};
+pub use convert_paths_to_uses::PathUses;
+pub use merge_transforms::TransformPrecision;
+pub use remove_descriptions::{DescriptionElement, KeptDescriptions};
+pub use remove_editor_namespace_data::EditorNamespaces;
+pub use round_floats::CoordinatePrecision;
+
+fn unknown_optimization(name: &str) -> Error {
+    Error::UnknownOptimization(name.to_string())
+}
+
+/// Function in [`passes`] applying a single optimization, with its parameters if it has any.
+macro_rules! pass {
+    ($name:ident, $doc:literal) => {
+        #[doc = $doc]
+        pub fn $name(document: &mut Document) {
+            document.map_nodes(super::$name);
+        }
+    };
+    ($name:ident, $doc:literal, $parameters:ty) => {
+        #[doc = $doc]
+        pub fn $name(document: &mut Document, parameters: &$parameters) {
+            document.map_nodes(|nodes| super::$name(nodes, parameters));
+        }
+    };
+}
+
+/// Built-in optimization with the current parameters, if it has any.
+macro_rules! builtin {
+    ($parameters:ident, $precision:ident, $name:ident, $kind:ident, $doc:literal) => {
+        Arc::new(Builtin {
+            name: stringify!($name),
+            description: $doc,
+            kind: Kind::$kind,
+            parameters: (),
+            optimize: |nodes, ()| $name(nodes),
+        })
+    };
+    ($parameters:ident, $precision:ident, $name:ident, $kind:ident, $doc:literal, $type:ty) => {
+        Arc::new(Builtin {
+            name: stringify!($name),
+            description: $doc,
+            kind: Kind::$kind,
+            parameters: $parameters.$name.clone().with_precision($precision),
+            optimize: $name,
+        })
+    };
+}
 
 macro_rules! use_optimizations {
-    ([$([$regular_opt_name:ident, $disable_flag_name:ident, $regular_doc:literal,]),*],
-    [$([$precision_opt_name:ident, $precision_doc:literal,]),*]) => {
+    ($([$name:ident, $kind:ident, $doc:literal $(, $parameters:ty)?]),*) => {
         $(
-            mod $regular_opt_name;
-            use $regular_opt_name::$regular_opt_name;
+            mod $name;
+            use $name::$name;
         )*
 
-        $(
-            mod $precision_opt_name;
-            use $precision_opt_name::$precision_opt_name;
-        )*
+        /// Names of all optimizations in the default order, with their descriptions.
+        pub(crate) const ALL_OPTIMIZATIONS: &[(&str, &str)] = &[
+            $((stringify!($name), $doc),)*
+        ];
 
-        #[derive(clap::Parser)]
-        pub(crate) struct Optimizations {
-            $(
-                #[arg(long)]
-                #[doc = $regular_doc]
-                $regular_opt_name: bool,
-
-                #[arg(long, conflicts_with = stringify!($regular_opt_name))]
-                #[doc = "Disable the optimization."]
-                $disable_flag_name: bool,
-            )*
-
-            $(
-                #[arg(long)]
-                #[doc = $precision_doc]
-                $precision_opt_name: bool,
-            )*
-
-            #[arg(short, long, default_value("3"))]
-            #[doc = "Desired precision for lossy optimizations."]
-            precision: usize,
+        /// Optimizations which can be applied to a [`Document`](crate::Document) one at a time.
+        pub mod passes {
+            #[allow(clippy::wildcard_imports)]
+            use super::*;
+
+            $(pass!($name, $doc $(, $parameters)?);)*
+        }
+
+        /// Parameters of the built-in optimizations by their names, along with the precision of lossy ones without
+        /// their own.
+        #[derive(Clone, Debug, Default, PartialEq)]
+        pub(crate) struct Parameters {
+            pub(crate) precision: Option<usize>,
+            $($(pub(crate) $name: $parameters,)?)*
         }
 
         impl Optimizations {
-            pub(crate) fn apply(&self, mut nodes: Vec<Node>, default_all: bool) -> Vec<Node> {
-                $(
-                    if self.$regular_opt_name || (default_all && !self.$disable_flag_name) {
-                        nodes = $regular_opt_name(nodes);
-                    }
-                )*
-
-                $(
-                    if self.$precision_opt_name {
-                        nodes = $precision_opt_name(nodes, self.precision);
-                    }
-                )*
-
-                nodes
+            /// Returns the built-in optimizations with the current parameters, and their kinds.
+            fn builtins(&self) -> Vec<(Arc<dyn Optimization>, Kind)> {
+                let parameters = &self.parameters;
+                let precision = parameters.precision.unwrap_or(self.preset.precision());
+                vec![
+                    $((
+                        builtin!(parameters, precision, $name, $kind, $doc $(, $parameters)?),
+                        Kind::$kind,
+                    ),)*
+                ]
             }
-
-            $(
-                #[cfg(test)]
-                #[allow(dead_code)]
-                pub(crate) fn $disable_flag_name(&self) -> bool {
-                    self.$disable_flag_name
-                }
-            )*
         }
     };
 }
 
 use_optimizations!(
-    [[
+    [
         remove_attribute_whitespace,
-        no_remove_attribute_whitespace,
-        "Remove excess whitespace from attributes.",
+        Safe,
+        "Remove excess whitespace from attributes."
     ],
     [
         remove_whitespace_outside_tags,
-        no_remove_whitespace_outside_tags,
-        "Remove excess whitespace from outside of tags. Leaves whitespace between `<text>` tags, as it may be rendered.",
+        Safe,
+        "Remove excess whitespace from outside of tags. Leaves whitespace between `<text>` tags, as it may be rendered."
     ],
     [
         ellipses_to_circles,
-        no_ellipses_to_circles,
-        "Convert ellipses to circles if their `rx` and `ry` are equal.",
+        Structural,
+        "Convert ellipses to circles if their `rx` and `ry` are equal."
     ],
     [
         remove_comments,
-        no_remove_comments,
-        "Remove all comments.",
+        Safe,
+        "Remove all comments."
     ],
     [
         remove_descriptions,
-        no_remove_descriptions,
-        "Remove `<title>`, `<metadata>`, `<desc>` tags and their contents.",
+        Structural,
+        "Remove `<title>`, `<metadata>`, `<desc>` tags and their contents, except the ones given with `--keep-description`.",
+        KeptDescriptions
     ],
     [
         remove_useless_groups,
-        no_remove_useless_groups,
-        "Remove groups that contain a single node or no nodes.",
+        Structural,
+        "Remove groups that contain a single node or no nodes."
     ],
     [
         remove_empty_attributes,
-        no_remove_empty_attributes,
-        "Remove attributes whose value is an empty string.",
+        Safe,
+        "Remove attributes whose value is an empty string."
     ],
     [
         remove_empty_texts,
-        no_remove_empty_texts,
-        "Remove empty `<text>`, `<tspan>`, `<tref>` tags.",
+        Structural,
+        "Remove empty `<text>`, `<tspan>`, `<tref>` tags."
     ],
     [
         shorten_ids,
-        no_shorten_ids,
-        "Convert id names to be as short as possible. New names will only be created from latin alphabet letters and digits.",
+        Structural,
+        "Convert id names to be as short as possible. New names will only be created from latin alphabet letters and digits."
     ],
     [
         remove_useless_ids,
-        no_remove_useless_ids,
-        "Removed unused ids.",
+        Structural,
+        "Removed unused ids."
     ],
     [
         sort_attributes,
-        no_sort_attributes,
-        "Sorts attributes by name.",
+        Safe,
+        "Sorts attributes by name."
     ],
     [
         extract_common_attributes,
-        no_extract_common_attributes,
-        "Extract common attributes in a group into the group.",
+        Structural,
+        "Extract common attributes in a group into the group."
     ],
     [
         remove_unused_defs,
-        no_remove_unused_defs,
-        "Remove defined objects which are not used anywhere.",
+        Structural,
+        "Remove defined objects which are not used anywhere."
     ],
     [
         remove_dimensions,
-        no_remove_dimensions,
-        "Remove width and height if they are equal to values in viewBox.",
+        Structural,
+        "Remove width and height if they are equal to values in viewBox."
     ],
     [
         remove_empty_containers,
-        no_remove_empty_containers,
-        "Remove empty container elements.",
+        Structural,
+        "Remove empty container elements."
     ],
     [
         remove_hidden_elements,
-        no_remove_hidden_elements,
-        "Remove elements which would not be rendered.",
+        Structural,
+        "Remove elements which would not be rendered."
     ],
     [
         remove_useless_stroke_and_fill,
-        no_remove_useless_stroke_and_fill,
-        "Remove fill and stroke attributes which would not be rendered.",
+        Safe,
+        "Remove fill and stroke attributes which would not be rendered."
     ],
     [
         convert_paths_to_uses,
-        no_convert_paths_to_uses,
-        "Replace identical paths with `<use>`s of a single path.",
+        Structural,
+        "Replace identical paths with `<use>`s of a single path, if it saves at least `--min-use-saving` bytes.",
+        PathUses
     ],
     [
         remove_editor_namespace_data,
-        no_remove_editor_namespace_data,
-        "Remove editor namespaces, elements and attributes.",
+        Structural,
+        "Remove elements and attributes from namespaces of known editors (Inkscape, Sodipodi, Adobe Illustrator, Sketch, Figma, Corel) and additional namespaces given with `--remove-namespace`.",
+        EditorNamespaces
     ],
     [
         merge_consecutive_paths,
-        no_merge_consecutive_paths,
-        "Merge consecutive paths if their attributes match.",
-    ]], [[
+        Structural,
+        "Merge consecutive paths if their attributes match."
+    ],
+    [
+        minify_animations,
+        Structural,
+        "Minify numbers and colors in SMIL animation values without changing them and remove `<animate>`s which do not change their target. Values are rounded by `--round-floats`."
+    ],
+    [
+        remove_unused_namespaces,
+        Safe,
+        "Remove namespace declarations whose prefixes are not used in the subtree they are declared for."
+    ],
+    [
+        convert_to_svg2,
+        OptIn,
+        "Output SVG 2: replace `xlink:href` with `href` and remove `version`, `baseProfile` and redundant `xml:space` attributes (disabled by default)."
+    ],
+    [
         round_floats,
+        Lossy,
         "Round floating point numbers to specified precision (disabled by default).",
-    ], [
+        CoordinatePrecision
+    ],
+    [
         merge_transforms,
+        Lossy,
         "Merge transform attribute components into one matrix (disabled by default).",
-    ]]
+        TransformPrecision
+    ]
 );
 
+/// Registry of the optimizations to apply. Built-in optimizations are registered when it is created, and registered
+/// again with their new parameters when they change.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Optimizations {
+    preset: Preset,
+    parameters: Parameters,
+    passes: Vec<String>,
+    /// Built-in optimizations followed by custom ones, in the default order.
+    registry: Vec<Registered>,
+}
+
+impl Default for Optimizations {
+    fn default() -> Self {
+        let mut optimizations = Self {
+            preset: Preset::default(),
+            parameters: Parameters::default(),
+            passes: vec![],
+            registry: vec![],
+        };
+        optimizations.register_builtins();
+        optimizations
+    }
+}
+
+impl Optimizations {
+    /// Registers the built-in optimizations, or replaces the registered ones, keeping whether they are enabled.
+    fn register_builtins(&mut self) {
+        for (optimization, kind) in self.builtins() {
+            match self
+                .registry
+                .iter_mut()
+                .find(|registered| registered.name() == optimization.name())
+            {
+                Some(registered) => registered.optimization = optimization,
+                None => self.registry.push(Registered {
+                    optimization,
+                    kind,
+                    enabled: None,
+                }),
+            }
+        }
+    }
+
+    pub(crate) fn set_preset(&mut self, preset: Preset) {
+        self.preset = preset;
+        self.register_builtins();
+    }
+
+    pub(crate) fn set_parameters(&mut self, update: impl FnOnce(&mut Parameters)) {
+        update(&mut self.parameters);
+        self.register_builtins();
+    }
+
+    /// Adds a custom optimization after the built-in ones. Lossy ones are disabled by default, and the safe preset
+    /// enables none, as they may change the structure of the document.
+    pub(crate) fn register(&mut self, optimization: Arc<dyn Optimization>) -> Result<()> {
+        let name = optimization.name();
+        if self.is_optimization(name) {
+            return Err(Error::DuplicateOptimization(name.to_string()));
+        }
+
+        self.registry.push(Registered {
+            kind: if optimization.lossy() {
+                Kind::Lossy
+            } else {
+                Kind::Structural
+            },
+            optimization,
+            enabled: None,
+        });
+        Ok(())
+    }
+
+    fn is_optimization(&self, name: &str) -> bool {
+        self.registry.iter().any(|registered| registered.name() == name)
+    }
+
+    pub(crate) fn set_passes(&mut self, names: &[&str]) -> Result<()> {
+        if let Some(name) = names.iter().find(|name| !self.is_optimization(name)) {
+            return Err(unknown_optimization(name));
+        }
+
+        self.passes = names.iter().map(ToString::to_string).collect();
+        Ok(())
+    }
+
+    pub(crate) fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let registered = self
+            .registry
+            .iter_mut()
+            .find(|registered| registered.name() == name)
+            .ok_or_else(|| unknown_optimization(name))?;
+        registered.enabled = Some(enabled);
+
+        Ok(())
+    }
+
+    /// Calls `on_applied` with each applied optimization and the nodes it returned.
+    pub(crate) fn apply(
+        &self,
+        nodes: Vec<Node>,
+        default_all: bool,
+        mut on_applied: impl FnMut(&dyn Optimization, &[Node]),
+    ) -> Vec<Node> {
+        let mut document = Document { nodes };
+        for optimization in self.enabled_optimizations(default_all) {
+            optimization.apply(&mut document);
+            on_applied(optimization, &document.nodes);
+        }
+
+        document.nodes
+    }
+
+    /// Returns the optimizations to apply, in order.
+    fn enabled_optimizations(&self, default_all: bool) -> Vec<&dyn Optimization> {
+        if !self.passes.is_empty() {
+            return self
+                .passes
+                .iter()
+                .filter_map(|pass| {
+                    self.registry
+                        .iter()
+                        .find(|registered| registered.name() == pass)
+                })
+                .map(|registered| registered.optimization.as_ref())
+                .collect();
+        }
+
+        self.registry
+            .iter()
+            .filter(|registered| {
+                registered.enabled.unwrap_or_else(|| {
+                    default_all
+                        && self
+                            .preset
+                            .enables(registered.kind)
+                })
+            })
+            .map(|registered| registered.optimization.as_ref())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test {
     use super::common::test::test_optimize;
+    use super::{
+        DescriptionElement, EditorNamespaces, KeptDescriptions, Optimizations, Parameter,
+        ParameterValue, PathUses, Preset, TransformPrecision,
+    };
+    use crate::document::Document;
+    use crate::error::Result;
     use crate::node::Node;
     use crate::parser::Parser;
     use crate::writer::SVGWriter;
@@ -202,4 +411,172 @@ pub(crate) mod test {
         <g><g/></g></svg>
         "#
     );
+
+    fn applied_optimizations(optimizations: &Optimizations, default_all: bool) -> Vec<&'static str> {
+        let mut applied = vec![];
+        optimizations.apply(vec![], default_all, |optimization, _| {
+            applied.push(optimization.name());
+        });
+        applied
+    }
+
+    fn with_preset(preset: Preset) -> Optimizations {
+        let mut optimizations = Optimizations::default();
+        optimizations.set_preset(preset);
+        optimizations
+    }
+
+    #[test]
+    fn test_presets() -> Result<()> {
+        let default = applied_optimizations(&Optimizations::default(), true);
+        assert!(default.contains(&"shorten_ids"));
+        assert!(!default.contains(&"round_floats"));
+
+        let safe = applied_optimizations(&with_preset(Preset::Safe), true);
+        assert!(safe.contains(&"remove_comments"));
+        assert!(!safe.contains(&"shorten_ids"));
+
+        let mut safe_with_ids = with_preset(Preset::Safe);
+        safe_with_ids.set_enabled("shorten_ids", true)?;
+        assert!(applied_optimizations(&safe_with_ids, true).contains(&"shorten_ids"));
+
+        let mut aggressive = with_preset(Preset::Aggressive);
+        aggressive.set_enabled("round_floats", false)?;
+        let aggressive = applied_optimizations(&aggressive, true);
+        assert!(aggressive.contains(&"merge_transforms"));
+        assert!(!aggressive.contains(&"round_floats"));
+
+        let mut disabled = with_preset(Preset::Aggressive);
+        disabled.set_enabled("round_floats", true)?;
+        assert_eq!(applied_optimizations(&disabled, false), vec!["round_floats"]);
+
+        Ok(())
+    }
+
+    fn element_names(document: &mut Document) -> Vec<(String, Option<String>)> {
+        let mut names = vec![];
+        document.for_each_element(|element| {
+            names.push((element.name(), element.attribute("id").map(ToString::to_string)));
+        });
+        names
+    }
+
+    #[test]
+    fn test_safe_preset() -> Result<()> {
+        let mut document: Document = r##"<svg xmlns="http://www.w3.org/2000/svg"
+            xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="10" height="10" viewBox="0 0 10 10">
+            <title>Title</title>
+            <defs><linearGradient id="unused_gradient"/></defs>
+            <inkscape:grid/>
+            <g><ellipse id="used_ellipse" cx="1" cy="1" rx="2" ry="2"/></g>
+            <use href="#used_ellipse"/>
+            <g fill="red"><path d="M0 0h1" fill="red"/><path d="M0 0h1" fill="red"/></g>
+            <text></text>
+            <rect width="0" height="1"/>
+            <g/>
+            <animate attributeName="fill" from="red" to="red"/>
+            </svg>"##
+            .parse()?;
+        let names = element_names(&mut document);
+
+        let optimizations = with_preset(Preset::Safe);
+        document.map_nodes(|nodes| optimizations.apply(nodes, true, |_, _| {}));
+        assert_eq!(element_names(&mut document), names);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_passes() -> Result<()> {
+        let mut optimizations = Optimizations::default();
+        optimizations.set_passes(&["remove_comments", "round_floats", "remove_comments"])?;
+        assert_eq!(
+            applied_optimizations(&optimizations, false),
+            vec!["remove_comments", "round_floats", "remove_comments"]
+        );
+
+        let mut optimizations = Optimizations::default();
+        optimizations.set_passes(&["shorten_ids"])?;
+        optimizations.set_enabled("shorten_ids", false)?;
+        assert_eq!(applied_optimizations(&optimizations, true), vec!["shorten_ids"]);
+
+        assert!(Optimizations::default().set_passes(&["shorten-ids"]).is_err());
+
+        Ok(())
+    }
+
+    fn parameters(optimizations: &Optimizations, name: &str) -> Vec<Parameter> {
+        optimizations
+            .registry
+            .iter()
+            .find(|registered| registered.name() == name)
+            .map(|registered| registered.optimization.parameters())
+            .unwrap_or_default()
+    }
+
+    fn number(name: &'static str, number: usize) -> Parameter {
+        Parameter {
+            name,
+            value: ParameterValue::Number(number),
+        }
+    }
+
+    fn list(name: &'static str, names: &[&str]) -> Parameter {
+        Parameter {
+            name,
+            value: ParameterValue::List(names.iter().map(ToString::to_string).collect()),
+        }
+    }
+
+    #[test]
+    fn test_parameters() {
+        let mut optimizations = with_preset(Preset::Icons);
+        optimizations.set_parameters(|parameters| {
+            parameters.merge_transforms = TransformPrecision::new(4);
+        });
+        assert_eq!(
+            parameters(&optimizations, "round_floats"),
+            vec![number("coordinate_precision", 1)]
+        );
+        assert_eq!(
+            parameters(&optimizations, "merge_transforms"),
+            vec![number("transform_precision", 4)]
+        );
+
+        let mut optimizations = Optimizations::default();
+        optimizations.set_parameters(|parameters| parameters.precision = Some(2));
+        assert_eq!(
+            parameters(&optimizations, "round_floats"),
+            vec![number("coordinate_precision", 2)]
+        );
+
+        let mut optimizations = Optimizations::default();
+        optimizations.set_parameters(|parameters| {
+            parameters.remove_descriptions = KeptDescriptions::default()
+                .keep_description(DescriptionElement::Title)
+                .keep_description(DescriptionElement::Desc);
+            parameters.convert_paths_to_uses = PathUses::default().min_use_saving(20);
+            parameters.remove_editor_namespace_data =
+                EditorNamespaces::default().remove_namespace("http://example.com/a");
+        });
+        assert_eq!(
+            parameters(&optimizations, "remove_descriptions"),
+            vec![list("keep_description", &["title", "desc"])]
+        );
+        assert_eq!(
+            parameters(&optimizations, "convert_paths_to_uses"),
+            vec![number("min_use_saving", 20)]
+        );
+        assert_eq!(
+            parameters(&optimizations, "remove_editor_namespace_data"),
+            vec![list("remove_namespace", &["http://example.com/a"])]
+        );
+
+        // Parameters which are not set are left out
+        let optimizations = Optimizations::default();
+        assert!(parameters(&optimizations, "remove_descriptions").is_empty());
+        assert!(parameters(&optimizations, "convert_paths_to_uses").is_empty());
+        assert!(parameters(&optimizations, "remove_editor_namespace_data").is_empty());
+        assert!(parameters(&optimizations, "remove_comments").is_empty());
+    }
 }
@@ -18,7 +18,7 @@ pub(crate) mod test {
 
                 let buffer = Vec::new();
                 let mut writer = SVGWriter::new(buffer);
-                writer.write(nodes)?;
+                writer.write(&nodes)?;
 
                 let actual = String::from_utf8(writer.into_inner()).unwrap();
 
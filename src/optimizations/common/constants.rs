@@ -1,5 +1,7 @@
 pub(crate) const ID_NAME: &str = "id";
 pub(crate) const HREF_NAME: &str = "href";
+pub(crate) const BEGIN_NAME: &str = "begin";
+pub(crate) const END_NAME: &str = "end";
 pub(crate) const CX_NAME: &str = "cx";
 pub(crate) const CY_NAME: &str = "cy";
 pub(crate) const RX_NAME: &str = "rx";
@@ -12,6 +14,21 @@ pub(crate) const HEIGHT_NAME: &str = "height";
 pub(crate) const VIEWBOX_NAME: &str = "viewBox";
 pub(crate) const DISPLAY_NAME: &str = "display";
 pub(crate) const OPACITY_NAME: &str = "opacity";
+pub(crate) const VISIBILITY_NAME: &str = "visibility";
+pub(crate) const ATTRIBUTE_NAME_NAME: &str = "attributeName";
+pub(crate) const VALUES_NAME: &str = "values";
+pub(crate) const KEY_TIMES_NAME: &str = "keyTimes";
+pub(crate) const KEY_SPLINES_NAME: &str = "keySplines";
+pub(crate) const FROM_NAME: &str = "from";
+pub(crate) const TO_NAME: &str = "to";
+pub(crate) const BY_NAME: &str = "by";
+pub(crate) const PATH_NAME: &str = "path";
+pub(crate) const ADDITIVE_NAME: &str = "additive";
+pub(crate) const ACCUMULATE_NAME: &str = "accumulate";
+pub(crate) const STYLE_NAME: &str = "style";
+pub(crate) const VERSION_NAME: &str = "version";
+pub(crate) const BASE_PROFILE_NAME: &str = "baseProfile";
+pub(crate) const SPACE_NAME: &str = "space";
 pub(crate) const PATH_DATA_NAME: &str = "d";
 pub(crate) const POINTS_NAME: &str = "points";
 pub(crate) const STROKE_OPACITY_NAME: &str = "stroke-opacity";
@@ -22,3 +39,9 @@ pub(crate) const TRANSFORM_NAME: &str = "transform";
 pub(crate) const PATH_LENGTH_NAME: &str = "pathLength";
 
 pub(crate) const NONE_VAL: &str = "none";
+pub(crate) const DEFAULT_VAL: &str = "default";
+pub(crate) const PRESERVE_VAL: &str = "preserve";
+
+pub(crate) const XML_PREFIX: &str = "xml";
+
+pub(crate) const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
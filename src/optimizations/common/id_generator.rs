@@ -1,4 +1,12 @@
 use super::iter::EasyIter;
+use itertools::Itertools;
+
+/// Checks whether the id could be mistaken for a hex color in CSS. Such ids are left unchanged when shortening.
+pub(crate) fn is_hex_color_prefix(id: &str) -> bool {
+    id.chars()
+        .all(|char| "abcdefABCDEF0123456789".chars().contains(&char))
+        && id.len() <= 6
+}
 
 pub(crate) struct IdGenerator {
     base_characters: Vec<char>,
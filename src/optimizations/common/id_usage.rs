@@ -1,7 +1,7 @@
-use super::constants::{HREF_NAME, ID_NAME};
+use super::constants::{ATTRIBUTE_NAME_NAME, BEGIN_NAME, END_NAME, HREF_NAME, ID_NAME};
 use crate::node::{ChildlessNodeType, Node, RegularNodeType};
+use lazy_regex::{Regex, regex};
 use std::collections::BTreeMap;
-use std::iter::repeat;
 use xml::attribute::OwnedAttribute;
 
 pub(crate) fn find_attribute<'a>(
@@ -45,121 +45,317 @@ pub(crate) fn find_ids_for_subtree(nodes: &Vec<Node>) -> Vec<String> {
     ids
 }
 
-fn find_id_usage_in_attribute(attribute: &OwnedAttribute, id_map: &mut BTreeMap<String, bool>) {
-    match attribute.name.local_name.as_str() {
-        HREF_NAME => {
-            if let Some((first, rest)) = attribute.value.split_once('#')
-                && first.is_empty()
-                && let Some(value_in_map) = id_map.get_mut(rest)
-            {
-                *value_in_map = true;
+/// Location of a node in the document, given as indices of consecutive children starting from the root.
+pub(crate) type NodeLocation = Vec<usize>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum ReferenceKind {
+    /// `href="#id"` attribute.
+    Href,
+    /// `url(#id)` in an attribute value or a `<style>` stylesheet.
+    Url,
+    /// Other `#id` in a `<style>` stylesheet, which is a selector or a hex color.
+    Stylesheet,
+    /// SMIL timing value such as `begin="id.end"`.
+    Animation,
+}
+
+/// Single reference to an id.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct IdReference {
+    /// Location of the referring element. For stylesheets, this is the `<style>` element.
+    pub(crate) location: NodeLocation,
+    pub(crate) kind: ReferenceKind,
+}
+
+fn href_reference(value: &str) -> Option<&str> {
+    value.strip_prefix('#')
+}
+
+fn url_references(value: &str) -> impl Iterator<Item = &str> {
+    regex!(r"url\(#([^)]+)\)")
+        .captures_iter(value)
+        .filter_map(|captures| captures.get(1))
+        .map(|id| id.as_str())
+}
+
+fn stylesheet_references(text: &str) -> impl Iterator<Item = (&str, ReferenceKind)> {
+    regex!(r"(url\(\s*)?#([\w-]+)")
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let kind = if captures.get(1).is_some() {
+                ReferenceKind::Url
+            } else {
+                ReferenceKind::Stylesheet
+            };
+            captures.get(2).map(|id| (id.as_str(), kind))
+        })
+}
+
+/// Matches a single SMIL timing value referencing an element, such as `foo.end` or `bar.click+1s`.
+/// Captures leading whitespace, the id and the beginning of the event name.
+///
+/// Dots in ids are escaped with a backslash. Clock values like `0.5s` are not matched, as the event name
+/// following the dot has to start with a letter.
+pub(crate) fn animation_reference_regex() -> &'static Regex {
+    regex!(r"^(\s*)((?:[^.\s\\]|\\.)+)(\.[A-Za-z])")
+}
+
+/// Finds ids in semicolon separated SMIL timing values such as `foo.end; bar.click+1s`.
+fn animation_references(value: &str) -> impl Iterator<Item = String> {
+    value.split(';').filter_map(|entry| {
+        animation_reference_regex()
+            .captures(entry)
+            .and_then(|captures| captures.get(2))
+            .map(|id| id.as_str().replace('\\', ""))
+    })
+}
+
+/// Index of id definitions and references in a document, built in a single pass over the node tree.
+///
+/// References are read from `href` attributes, `url(#...)` values of other attributes, `<style>` stylesheets
+/// and SMIL `begin`/`end` attributes.
+pub(crate) struct IdUsage {
+    definitions: BTreeMap<String, Vec<NodeLocation>>,
+    references: BTreeMap<String, Vec<IdReference>>,
+    /// Names of attributes animated by `<animate>`s and `<set>`s targeting the id with `href`.
+    animated_attributes: BTreeMap<String, Vec<String>>,
+}
+
+impl IdUsage {
+    pub(crate) fn new(nodes: &[Node]) -> Self {
+        let mut id_usage = Self {
+            definitions: BTreeMap::new(),
+            references: BTreeMap::new(),
+            animated_attributes: BTreeMap::new(),
+        };
+        id_usage.add_nodes(nodes, &mut vec![]);
+        id_usage
+    }
+
+    fn add_reference(&mut self, id: &str, location: &[usize], kind: ReferenceKind) {
+        self.references
+            .entry(id.into())
+            .or_default()
+            .push(IdReference {
+                location: location.to_vec(),
+                kind,
+            });
+    }
+
+    fn add_attribute(&mut self, attribute: &OwnedAttribute, location: &[usize]) {
+        match attribute.name.local_name.as_str() {
+            HREF_NAME => {
+                if let Some(id) = href_reference(&attribute.value) {
+                    self.add_reference(id, location, ReferenceKind::Href);
+                }
             }
-        }
-        _ => {
-            for (id, value_in_map) in id_map.iter_mut() {
-                if attribute.value.contains(&format!("url(#{id})")) {
-                    *value_in_map = true;
+            BEGIN_NAME | END_NAME => {
+                for id in animation_references(&attribute.value) {
+                    self.add_reference(&id, location, ReferenceKind::Animation);
+                }
+            }
+            _ => {
+                for id in url_references(&attribute.value) {
+                    self.add_reference(id, location, ReferenceKind::Url);
                 }
             }
         }
     }
-}
 
-fn find_id_usages_in_style_node(style_child: &Node, id_map: &mut BTreeMap<String, bool>) {
-    if let Node::ChildlessNode {
-        node_type: ChildlessNodeType::Text(text, ..),
-    } = style_child
-    {
-        for (id, value_in_map) in id_map.iter_mut() {
-            if text.contains(&format!("#{id}")) {
-                *value_in_map = true;
-            }
+    fn add_animation_target(&mut self, attributes: &[OwnedAttribute]) {
+        let target = find_attribute(attributes, HREF_NAME).and_then(|href| href_reference(href));
+        if let (Some(id), Some(attribute_name)) =
+            (target, find_attribute(attributes, ATTRIBUTE_NAME_NAME))
+        {
+            self.animated_attributes
+                .entry(id.into())
+                .or_default()
+                .push(attribute_name.clone());
         }
     }
-}
 
-fn find_id_usages_for_node(node: &Node, id_map: &mut BTreeMap<String, bool>) {
-    if let Node::RegularNode {
-        node_type,
-        attributes,
-        children,
-        ..
-    } = node
-    {
-        for attribute in attributes {
-            find_id_usage_in_attribute(attribute, id_map);
+    fn add_stylesheet(&mut self, style_children: &[Node], location: &[usize]) {
+        for child in style_children {
+            if let Node::ChildlessNode {
+                node_type: ChildlessNodeType::Text(text, ..),
+            } = child
+            {
+                for (id, kind) in stylesheet_references(text) {
+                    self.add_reference(id, location, kind);
+                }
+            }
         }
+    }
 
-        let find_func = if let RegularNodeType::Style = node_type {
-            find_id_usages_in_style_node
-        } else {
-            find_id_usages_for_node
-        };
+    fn add_nodes(&mut self, nodes: &[Node], location: &mut NodeLocation) {
+        for (index, node) in nodes.iter().enumerate() {
+            if let Node::RegularNode {
+                node_type,
+                attributes,
+                children,
+                ..
+            } = node
+            {
+                location.push(index);
+
+                for attribute in attributes {
+                    if attribute.name.local_name == ID_NAME {
+                        self.definitions
+                            .entry(attribute.value.clone())
+                            .or_default()
+                            .push(location.clone());
+                    }
+                    self.add_attribute(attribute, location);
+                }
+
+                if let RegularNodeType::Animate | RegularNodeType::Set = node_type {
+                    self.add_animation_target(attributes);
+                }
 
-        for child in children {
-            find_func(child, id_map);
+                if let RegularNodeType::Style = node_type {
+                    self.add_stylesheet(children, location);
+                } else {
+                    self.add_nodes(children, location);
+                }
+
+                location.pop();
+            }
         }
     }
-}
 
-pub(crate) fn make_id_usage_map(nodes: &Vec<Node>) -> BTreeMap<String, bool> {
-    let ids = find_ids_for_subtree(nodes);
-    let mut id_usage_map = ids.into_iter().zip(repeat(false)).collect();
+    /// Iterates over all defined ids and locations of the elements defining them.
+    pub(crate) fn definitions(&self) -> impl Iterator<Item = (&String, &[NodeLocation])> {
+        self.definitions
+            .iter()
+            .map(|(id, locations)| (id, locations.as_slice()))
+    }
 
-    for node in nodes {
-        find_id_usages_for_node(node, &mut id_usage_map);
+    /// Iterates over all references, including the ones to ids which are not defined.
+    pub(crate) fn references(&self) -> impl Iterator<Item = (&String, &IdReference)> {
+        self.references
+            .iter()
+            .flat_map(|(id, references)| references.iter().map(move |reference| (id, reference)))
+    }
+
+    pub(crate) fn is_defined(&self, id: &str) -> bool {
+        self.definitions.contains_key(id)
+    }
+
+    /// Returns names of attributes animated by elements targeting the id with `href`.
+    pub(crate) fn animated_attributes(&self, id: &str) -> &[String] {
+        self.animated_attributes.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn referrers(&self, id: &str) -> &[IdReference] {
+        self.references.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn reference_count(&self, id: &str) -> usize {
+        self.referrers(id).len()
+    }
+
+    pub(crate) fn is_used(&self, id: &str) -> bool {
+        self.reference_count(id) > 0
     }
 
-    id_usage_map
+    /// Checks whether the id is referenced by an element which is not a descendant of the node at `location`.
+    pub(crate) fn is_used_outside(&self, id: &str, location: &[usize]) -> bool {
+        self.referrers(id)
+            .iter()
+            .any(|reference| !reference.location.starts_with(location))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::Parser;
     use xml::name::OwnedName;
 
+    fn id_usage_for_attribute(name: &str, value: &str) -> IdUsage {
+        let mut id_usage = IdUsage {
+            definitions: BTreeMap::new(),
+            references: BTreeMap::new(),
+            animated_attributes: BTreeMap::new(),
+        };
+        id_usage.add_attribute(
+            &OwnedAttribute {
+                name: OwnedName::local(name),
+                value: value.to_string(),
+            },
+            &[],
+        );
+        id_usage
+    }
+
     #[test]
     fn test_find_id_usage_with_href_starting_with_hash() {
-        let mut id_map = BTreeMap::new();
-        id_map.insert("test-id".to_string(), false);
+        let id_usage = id_usage_for_attribute(HREF_NAME, "#test-id");
 
-        let attribute = OwnedAttribute {
-            name: OwnedName::local(HREF_NAME),
-            value: "#test-id".to_string(),
-        };
+        assert!(id_usage.is_used("test-id"));
+    }
 
-        find_id_usage_in_attribute(&attribute, &mut id_map);
+    #[test]
+    fn test_find_id_usage_with_href_not_starting_with_hash() {
+        let id_usage = id_usage_for_attribute(HREF_NAME, "http://example.com#test-id");
 
-        assert!(*id_map.get("test-id").unwrap());
+        assert!(!id_usage.is_used("test-id"));
     }
 
     #[test]
-    fn test_find_id_usage_with_href_not_starting_with_hash() {
-        let mut id_map = BTreeMap::new();
-        id_map.insert("test-id".to_string(), false);
+    fn test_find_id_usage_with_url_reference() {
+        let id_usage = id_usage_for_attribute("fill", "url(#test-id)");
 
-        let attribute = OwnedAttribute {
-            name: OwnedName::local(HREF_NAME),
-            value: "http://example.com#test-id".to_string(),
-        };
+        assert!(id_usage.is_used("test-id"));
+    }
 
-        find_id_usage_in_attribute(&attribute, &mut id_map);
+    #[test]
+    fn test_find_id_usage_with_animation_timing() {
+        let id_usage = id_usage_for_attribute(
+            BEGIN_NAME,
+            r"0.5s; first.end; second.click+1s; third\.part.begin-2s; indefinite",
+        );
 
-        assert!(!*id_map.get("test-id").unwrap());
+        assert!(id_usage.is_used("first"));
+        assert!(id_usage.is_used("second"));
+        assert!(id_usage.is_used("third.part"));
+        assert!(!id_usage.is_used("0"));
+        assert!(!id_usage.is_used("indefinite"));
     }
 
     #[test]
-    fn test_find_id_usage_with_url_reference() {
-        let mut id_map = BTreeMap::new();
-        id_map.insert("test-id".to_string(), false);
+    fn test_reference_counts_and_locations() -> anyhow::Result<()> {
+        let test_string = r##"<svg xmlns="http://www.w3.org/2000/svg">
+            <style>#small { fill: #000066; }</style>
+            <rect id="small" fill="url(#gradient)"/>
+            <use href="#small"/>
+            </svg>"##;
+        let nodes = Parser::new(test_string.as_bytes())?.parse_document()?;
 
-        let attribute = OwnedAttribute {
-            name: OwnedName::local("fill"),
-            value: "url(#test-id)".to_string(),
-        };
+        let id_usage = IdUsage::new(&nodes);
 
-        find_id_usage_in_attribute(&attribute, &mut id_map);
+        assert_eq!(id_usage.reference_count("small"), 2);
+        assert_eq!(id_usage.reference_count("gradient"), 1);
+        assert_eq!(id_usage.reference_count("smal"), 0);
+        assert_eq!(
+            id_usage.referrers("small"),
+            [
+                IdReference {
+                    location: vec![0, 1],
+                    kind: ReferenceKind::Stylesheet,
+                },
+                IdReference {
+                    location: vec![0, 5],
+                    kind: ReferenceKind::Href,
+                },
+            ]
+        );
+        assert!(id_usage.is_used_outside("small", &[0, 3]));
+        assert!(!id_usage.is_used_outside("gradient", &[0, 3]));
+        assert!(id_usage.is_defined("small"));
+        assert!(!id_usage.is_defined("gradient"));
 
-        assert!(*id_map.get("test-id").unwrap());
+        Ok(())
     }
 }
@@ -1,11 +1,27 @@
 use super::{
-    constants::{HREF_NAME, ID_NAME},
+    constants::{BEGIN_NAME, END_NAME, HREF_NAME, ID_NAME},
+    id_usage::animation_reference_regex,
     iter::EasyIter,
 };
 use crate::node::{ChildlessNodeType, Node, RegularNodeType};
+use itertools::Itertools;
+use lazy_regex::Captures;
 use std::collections::BTreeMap;
 use xml::attribute::OwnedAttribute;
 
+fn replace_ids_in_animation_timing(value: &str, id_map: &BTreeMap<String, String>) -> String {
+    value
+        .split(';')
+        .map(|entry| {
+            animation_reference_regex().replace(entry, |captures: &Captures| {
+                let id = &captures[2];
+                let new_id = id_map.get(&id.replace('\\', "")).map_or(id, String::as_str);
+                format!("{}{new_id}{}", &captures[1], &captures[3])
+            })
+        })
+        .join(";")
+}
+
 fn replace_ids_in_attribute(
     mut attribute: OwnedAttribute,
     id_map: &BTreeMap<String, String>,
@@ -25,6 +41,9 @@ fn replace_ids_in_attribute(
                 attribute.value = format!("#{new_id}");
             }
         }
+        BEGIN_NAME | END_NAME => {
+            attribute.value = replace_ids_in_animation_timing(&attribute.value, id_map);
+        }
         _ => {
             for (id, new_id) in id_map {
                 attribute.value = attribute
@@ -134,6 +153,22 @@ mod tests {
         assert_eq!(result.value, "url(#new-id)");
     }
 
+    #[test]
+    fn test_replace_ids_in_animation_timing() {
+        let mut id_map = BTreeMap::new();
+        id_map.insert("old-id".to_string(), "new-id".to_string());
+        id_map.insert("old.id".to_string(), "g".to_string());
+
+        let attribute = OwnedAttribute {
+            name: OwnedName::local(BEGIN_NAME),
+            value: r"old-id.end; 0.5s;old\.id.click+1s; other.begin".to_string(),
+        };
+
+        let result = replace_ids_in_attribute(attribute, &id_map);
+
+        assert_eq!(result.value, "new-id.end; 0.5s;g.click+1s; other.begin");
+    }
+
     #[test]
     fn test_replace_id_attribute() {
         let mut id_map = BTreeMap::new();
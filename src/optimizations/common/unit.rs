@@ -1,5 +1,5 @@
 use super::id_usage::find_attribute;
-use lazy_regex::{regex, regex_replace};
+use lazy_regex::{Regex, regex, regex_replace};
 use xml::attribute::OwnedAttribute;
 
 fn unit_to_multiplier(unit: &str) -> Option<f64> {
@@ -41,9 +41,77 @@ pub(crate) fn round_float(number: f64, precision: usize) -> String {
     rounded.into_owned()
 }
 
+/// Shortens the textual representation of a number without changing its value, e.g. `+00.500e+01` to `.5e1`.
+pub(crate) fn minify_number(number: &str) -> String {
+    let (mantissa, exponent) = number.split_at(number.find(['e', 'E']).unwrap_or(number.len()));
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let integer = integer.trim_start_matches('0');
+    let fraction = fraction.trim_end_matches('0');
+
+    if integer.is_empty() && fraction.is_empty() {
+        return "0".into();
+    }
+
+    let mut minified = format!("{sign}{integer}");
+    if !fraction.is_empty() {
+        minified.push('.');
+        minified.push_str(fraction);
+    }
+
+    let exponent = exponent.get(1..).unwrap_or_default();
+    let (exponent_sign, exponent) = match exponent.strip_prefix('-') {
+        Some(exponent) => ("-", exponent),
+        None => ("", exponent.strip_prefix('+').unwrap_or(exponent)),
+    };
+    let exponent = exponent.trim_start_matches('0');
+    if !exponent.is_empty() {
+        minified.push('e');
+        minified.push_str(exponent_sign);
+        minified.push_str(exponent);
+    }
+
+    minified
+}
+
+/// Replaces each number matched by `number_regex` in the value.
+///
+/// A number directly followed by one starting with `.` is only separated from it by its own `.`, so a space is added
+/// if the replacement drops it, e.g. `0.0.5` becomes `0 .5` instead of `0.5`.
+pub(crate) fn replace_numbers(
+    value: &str,
+    number_regex: &Regex,
+    mut replace: impl FnMut(&str) -> String,
+) -> String {
+    let mut replaced = String::with_capacity(value.len());
+    let mut last_end = 0;
+    let mut previous_has_dot = true;
+
+    for number in number_regex.find_iter(value) {
+        let separator = &value[last_end..number.start()];
+        let replacement = replace(number.as_str());
+
+        if separator.is_empty() && !previous_has_dot && replacement.starts_with('.') {
+            replaced.push(' ');
+        }
+        replaced.push_str(separator);
+        replaced.push_str(&replacement);
+
+        previous_has_dot = replacement.contains('.');
+        last_end = number.end();
+    }
+    replaced.push_str(&value[last_end..]);
+
+    replaced
+}
+
 #[cfg(test)]
 mod tests {
-    use super::round_float;
+    use super::{minify_number, replace_numbers, round_float};
+    use lazy_regex::regex;
 
     #[test]
     fn test_rounding() {
@@ -53,4 +121,37 @@ mod tests {
         assert_eq!(round_float(-0.00001, 3), "0");
         assert_eq!(round_float(-0.072_971_243_366_440_5, 2), "-.07");
     }
+
+    #[test]
+    fn test_minify_number() {
+        assert_eq!(minify_number("10"), "10");
+        assert_eq!(minify_number("+00.500"), ".5");
+        assert_eq!(minify_number("-0.0"), "0");
+        assert_eq!(minify_number("-010.250"), "-10.25");
+        assert_eq!(minify_number("1.50E+02"), "1.5e2");
+        assert_eq!(minify_number("2e-05"), "2e-5");
+        assert_eq!(minify_number("3.0e0"), "3");
+    }
+
+    #[test]
+    fn test_replace_numbers() {
+        let number_regex = regex!(r"[+-]?(?:\d+\.?\d*|\.\d+)");
+
+        assert_eq!(
+            replace_numbers("M0.0.5L1.50 1", number_regex, minify_number),
+            "M0 .5L1.5 1"
+        );
+        assert_eq!(
+            replace_numbers("1.0.5", number_regex, minify_number),
+            "1 .5"
+        );
+        assert_eq!(
+            replace_numbers("0.50.5", number_regex, minify_number),
+            ".5.5"
+        );
+        assert_eq!(
+            replace_numbers("-1.0-.5", number_regex, minify_number),
+            "-1-.5"
+        );
+    }
 }
@@ -5,10 +5,58 @@ use super::common::{
     iter::EasyIter,
     replace_ids::replace_ids,
 };
+use super::registry::{Parameter, PassParameters};
 use crate::node::{Node, NodeNamespace, RegularNodeType};
 use std::collections::BTreeMap;
 use xml::{attribute::OwnedAttribute, name::OwnedName};
 
+/// Limits `--convert-paths-to-uses` to paths whose conversion is worth it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathUses {
+    min_use_saving: Option<usize>,
+}
+
+impl PathUses {
+    /// Converts identical paths only if it saves at least the given number of bytes.
+    #[must_use]
+    pub fn min_use_saving(mut self, bytes: usize) -> Self {
+        self.min_use_saving = Some(bytes);
+        self
+    }
+
+    /// Estimates the bytes saved by replacing all but one of `count` copies of the path with `<use>`s of `id`,
+    /// counting the attributes of the path, but not its children.
+    fn is_worth_converting(&self, path: &Node, count: u32, id: &str) -> bool {
+        let Some(min_saving) = self.min_use_saving else {
+            return true;
+        };
+        let Node::RegularNode { attributes, .. } = path else {
+            return false;
+        };
+
+        let path_size = "<path/>".len()
+            + attributes
+                .iter()
+                .filter(|attr| attr.name.local_name != ID_NAME)
+                .map(|attr| format!(r#" {}="{}""#, attr.name.borrow().to_repr(), attr.value).len())
+                .sum::<usize>();
+        let use_size = format!(r##"<use href="#{id}"/>"##).len();
+        let id_size = format!(r#" id="{id}""#).len();
+
+        let replaced = count as usize - 1;
+        let saving = (replaced * path_size).saturating_sub(replaced * use_size + id_size);
+        saving >= min_saving
+    }
+}
+
+impl PassParameters for PathUses {
+    fn parameters(&self) -> Vec<Parameter> {
+        Parameter::number("min_use_saving", self.min_use_saving)
+            .into_iter()
+            .collect()
+    }
+}
+
 fn are_equal_paths(node1: &Node, node2: &Node) -> bool {
     if let (
         Node::RegularNode {
@@ -69,14 +117,17 @@ fn find_path_usages(nodes: &[Node]) -> Vec<(Node, u32)> {
 fn prepare_map_for_paths(
     nodes: &Vec<Node>,
     path_usages: Vec<(Node, u32)>,
+    path_uses: &PathUses,
 ) -> Vec<(Node, String, bool)> {
     let used_ids = find_ids_for_subtree(nodes);
-    let id_generator = IdGenerator::new(used_ids);
+    let mut id_generator = IdGenerator::new(used_ids).peekable();
     path_usages
         .into_iter()
-        .filter_map(|(node, count)| (count > 1).then_some(node))
-        .zip(id_generator)
-        .map(|(path, id)| (path, id, false))
+        .filter(|(_, count)| *count > 1)
+        .filter_map(|(path, count)| {
+            let id = id_generator.next_if(|id| path_uses.is_worth_converting(&path, count, id))?;
+            Some((path, id, false))
+        })
         .collect()
 }
 
@@ -158,9 +209,9 @@ fn merge_paths_for_node(
     }
 }
 
-pub(crate) fn convert_paths_to_uses(nodes: Vec<Node>) -> Vec<Node> {
+pub(crate) fn convert_paths_to_uses(nodes: Vec<Node>, path_uses: &PathUses) -> Vec<Node> {
     let path_usages = find_path_usages(&nodes);
-    let mut paths_map = prepare_map_for_paths(&nodes, path_usages);
+    let mut paths_map = prepare_map_for_paths(&nodes, path_usages, path_uses);
     let mut id_map = BTreeMap::new();
 
     let new_nodes =
@@ -176,9 +227,17 @@ mod tests {
     use crate::parser::Parser;
     use crate::writer::SVGWriter;
 
+    fn test_convert_all(nodes: Vec<Node>) -> Vec<Node> {
+        convert_paths_to_uses(nodes, &PathUses::default())
+    }
+
+    fn test_convert_long_paths(nodes: Vec<Node>) -> Vec<Node> {
+        convert_paths_to_uses(nodes, &PathUses::default().min_use_saving(10))
+    }
+
     test_optimize!(
         test_convert_paths_to_uses,
-        convert_paths_to_uses,
+        test_convert_all,
         r##"<svg xmlns="http://www.w3.org/2000/svg">
         <path id="abcd1" d="M150 5 L75 200 L225 200 Z"/>
         <g>
@@ -203,7 +262,7 @@ mod tests {
 
     test_optimize!(
         test_convert_paths_to_uses_with_used_ids,
-        convert_paths_to_uses,
+        test_convert_all,
         r##"<svg xmlns="http://www.w3.org/2000/svg">
         <path id="abcd1" d="M150 5 L75 200 L225 200 Z"/>
         <path id="abcd2" d="M150 5 L75 200 L225 200 Z"/>
@@ -217,4 +276,21 @@ mod tests {
         <use href="#g"/>
         </svg>"##
     );
+
+    test_optimize!(
+        test_convert_paths_to_uses_min_saving,
+        test_convert_long_paths,
+        r#"<svg xmlns="http://www.w3.org/2000/svg">
+        <path d="M1 1h1"/>
+        <path d="M1 1h1"/>
+        <path d="M150 5 L75 200 L225 200 Z"/>
+        <path d="M150 5 L75 200 L225 200 Z"/>
+        </svg>"#,
+        r##"<svg xmlns="http://www.w3.org/2000/svg">
+        <path d="M1 1h1"/>
+        <path d="M1 1h1"/>
+        <path d="M150 5 L75 200 L225 200 Z" id="g"/>
+        <use href="#g"/>
+        </svg>"##
+    );
 }
@@ -0,0 +1,238 @@
+use super::common::{
+    constants::{
+        BASE_PROFILE_NAME, DEFAULT_VAL, HREF_NAME, SPACE_NAME, VERSION_NAME, XLINK_NAMESPACE,
+        XML_PREFIX,
+    },
+    iter::EasyIter,
+};
+use crate::node::{ChildlessNodeType, Node, RegularNodeType};
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+
+fn has_prefixed_name(attr: &OwnedAttribute, prefix: &str, local_name: &str) -> bool {
+    attr.name.prefix.as_deref() == Some(prefix) && attr.name.local_name == local_name
+}
+
+// Matched by namespace, as the xlink namespace may be bound to any prefix
+fn is_xlink_href(attr: &OwnedAttribute) -> bool {
+    attr.name.namespace.as_deref() == Some(XLINK_NAMESPACE) && attr.name.local_name == HREF_NAME
+}
+
+// If both `href` and `xlink:href` are present, `href` takes precedence
+fn convert_xlink_href(attributes: Vec<OwnedAttribute>) -> Vec<OwnedAttribute> {
+    let has_href = attributes
+        .iter()
+        .any(|attr| attr.name.prefix.is_none() && attr.name.local_name == HREF_NAME);
+
+    attributes.filter_map_to_vec(|mut attr| {
+        if !is_xlink_href(&attr) {
+            Some(attr)
+        } else if has_href {
+            None
+        } else {
+            attr.name = OwnedName::local(HREF_NAME);
+            Some(attr)
+        }
+    })
+}
+
+// Text content elements and elements with character data, e.g. unknown ones, can contain text `xml:space` applies to
+fn is_text_content(node_type: &RegularNodeType, children: &[Node]) -> bool {
+    matches!(
+        node_type,
+        RegularNodeType::Text | RegularNodeType::TSpan | RegularNodeType::TextPath
+    ) || children.iter().any(|child| {
+        matches!(
+            child,
+            Node::ChildlessNode {
+                node_type: ChildlessNodeType::Text(..),
+            }
+        )
+    })
+}
+
+fn contains_text(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::RegularNode {
+            node_type,
+            children,
+            ..
+        } => is_text_content(node_type, children) || contains_text(children),
+        Node::ChildlessNode { .. } => false,
+    })
+}
+
+// `xml:space` is redundant if it does not change the inherited value or there is no text it could apply to
+fn is_redundant_space(
+    attr: &OwnedAttribute,
+    inherited_space: &str,
+    node_type: &RegularNodeType,
+    children: &[Node],
+) -> bool {
+    has_prefixed_name(attr, XML_PREFIX, SPACE_NAME)
+        && (attr.value == inherited_space
+            || (!is_text_content(node_type, children) && !contains_text(children)))
+}
+
+fn is_svg_1_1_version(attr: &OwnedAttribute, node_type: &RegularNodeType) -> bool {
+    *node_type == RegularNodeType::Svg
+        && attr.name.prefix.is_none()
+        && [VERSION_NAME, BASE_PROFILE_NAME].contains(&attr.name.local_name.as_str())
+}
+
+fn convert_to_svg2_in_node(node: Node, inherited_space: &str) -> Node {
+    match node {
+        Node::RegularNode {
+            node_type,
+            namespace,
+            attributes,
+            children,
+        } => {
+            let space = attributes
+                .iter()
+                .find(|attr| has_prefixed_name(attr, XML_PREFIX, SPACE_NAME))
+                .map_or(inherited_space, |attr| attr.value.as_str())
+                .to_owned();
+
+            let attributes = convert_xlink_href(attributes).filter_to_vec(|attr| {
+                !is_svg_1_1_version(attr, &node_type)
+                    && !is_redundant_space(attr, inherited_space, &node_type, &children)
+            });
+
+            Node::RegularNode {
+                node_type,
+                namespace,
+                attributes,
+                children: children.map_to_vec(|child| convert_to_svg2_in_node(child, &space)),
+            }
+        }
+        other => other,
+    }
+}
+
+fn uses_namespace(nodes: &[Node], uri: &str) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::RegularNode {
+            namespace,
+            attributes,
+            children,
+            ..
+        } => {
+            namespace.parent_namespace.as_deref() == Some(uri)
+                || attributes
+                    .iter()
+                    .any(|attr| attr.name.namespace.as_deref() == Some(uri))
+                || uses_namespace(children, uri)
+        }
+        Node::ChildlessNode { .. } => false,
+    })
+}
+
+// Removes every prefix bound to the namespace
+fn remove_namespace_declaration(node: Node, uri: &str) -> Node {
+    match node {
+        Node::RegularNode {
+            node_type,
+            mut namespace,
+            attributes,
+            children,
+        } => {
+            namespace
+                .element_namespace
+                .0
+                .retain(|_, namespace_uri| namespace_uri != uri);
+
+            Node::RegularNode {
+                node_type,
+                namespace,
+                attributes,
+                children: children.map_to_vec(|child| remove_namespace_declaration(child, uri)),
+            }
+        }
+        other => other,
+    }
+}
+
+pub(crate) fn convert_to_svg2(nodes: Vec<Node>) -> Vec<Node> {
+    let nodes: Vec<Node> = nodes.map_to_vec(|node| convert_to_svg2_in_node(node, DEFAULT_VAL));
+
+    if uses_namespace(&nodes, XLINK_NAMESPACE) {
+        nodes
+    } else {
+        nodes.map_to_vec(|node| remove_namespace_declaration(node, XLINK_NAMESPACE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizations::common::test::test_optimize;
+    use crate::parser::Parser;
+    use crate::writer::SVGWriter;
+
+    test_optimize!(
+        test_convert_to_svg2,
+        convert_to_svg2,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" baseProfile="full">
+        <use xlink:href="#a" xml:space="preserve"/>
+        <use href="#b" xlink:href="#c"/>
+        <g xml:space="default"><text xml:space="preserve">A  B</text><text xml:space="default">C</text></g>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <use href="#a"/>
+        <use href="#b"/>
+        <g><text xml:space="preserve">A  B</text><text>C</text></g>
+        </svg>
+        "##
+    );
+
+    test_optimize!(
+        test_convert_to_svg2_keep_used_xlink,
+        convert_to_svg2,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" xml:space="preserve">
+        <a xlink:href="https://example.com" xlink:title="Example"><text>A  B</text></a>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" xml:space="preserve">
+        <a href="https://example.com" xlink:title="Example"><text>A  B</text></a>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_convert_to_svg2_keep_space_in_tspan,
+        convert_to_svg2,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <text>A<tspan xml:space="preserve">  B  C</tspan></text>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <text>A<tspan xml:space="preserve">  B  C</tspan></text>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_convert_to_svg2_xlink_namespace,
+        convert_to_svg2,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:x="http://www.w3.org/1999/xlink" xmlns:xlink="https://example.com/link">
+        <use x:href="#a"/>
+        <a xlink:href="https://example.com"/>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="https://example.com/link">
+        <use href="#a"/>
+        <a xlink:href="https://example.com"/>
+        </svg>
+        "##
+    );
+}
@@ -1,11 +1,43 @@
 use super::common::{
     constants::TRANSFORM_NAME, id_usage::find_attribute_mut, iter::EasyIter, unit::round_float,
 };
+use super::preset::DEFAULT_PRECISION;
+use super::registry::{Parameter, PassParameters};
 use crate::node::Node;
 use itertools::Itertools;
 use lazy_regex::regex;
 use nalgebra::{Matrix2, Matrix3, Vector2};
 
+/// Number of digits kept in matrices created by `--merge-transforms`. Defaults to `--precision`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransformPrecision {
+    transform_precision: Option<usize>,
+}
+
+impl TransformPrecision {
+    /// Rounds matrix components to the given number of decimal places.
+    #[must_use]
+    pub fn new(digits: usize) -> Self {
+        Self {
+            transform_precision: Some(digits),
+        }
+    }
+}
+
+impl PassParameters for TransformPrecision {
+    fn with_precision(self, precision: usize) -> Self {
+        Self {
+            transform_precision: self.transform_precision.or(Some(precision)),
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        Parameter::number("transform_precision", self.transform_precision)
+            .into_iter()
+            .collect()
+    }
+}
+
 fn matrix(arguments: &[f64]) -> Option<Matrix3<f64>> {
     if let [a, b, c, d, e, f] = arguments[..] {
         Some(Matrix3::from_vec(vec![a, b, 0., c, d, 0., e, f, 1.]))
@@ -132,17 +164,24 @@ fn merge_transforms_in_node(node: Node, precision: usize) -> Node {
                 node_type,
                 namespace,
                 attributes,
-                children: merge_transforms(children, precision),
+                children: merge_transforms_in_nodes(children, precision),
             }
         }
         other => other,
     }
 }
 
-pub(crate) fn merge_transforms(nodes: Vec<Node>, precision: usize) -> Vec<Node> {
+fn merge_transforms_in_nodes(nodes: Vec<Node>, precision: usize) -> Vec<Node> {
     nodes.map_to_vec(|node| merge_transforms_in_node(node, precision))
 }
 
+pub(crate) fn merge_transforms(nodes: Vec<Node>, precision: &TransformPrecision) -> Vec<Node> {
+    merge_transforms_in_nodes(
+        nodes,
+        precision.transform_precision.unwrap_or(DEFAULT_PRECISION),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,7 +190,7 @@ mod tests {
     use crate::writer::SVGWriter;
 
     fn test_merge(nodes: Vec<Node>) -> Vec<Node> {
-        merge_transforms(nodes, 2)
+        merge_transforms(nodes, &TransformPrecision::new(2))
     }
 
     test_optimize!(
@@ -0,0 +1,292 @@
+#[allow(clippy::wildcard_imports)]
+use super::common::{
+    constants::*,
+    id_usage::find_attribute,
+    iter::EasyIter,
+    unit::{minify_number, replace_numbers},
+};
+use crate::node::{ChildlessNodeType, Node, RegularNodeType};
+use itertools::Itertools;
+use lazy_regex::{regex, regex_is_match, regex_replace_all};
+use std::collections::HashSet;
+use xml::attribute::OwnedAttribute;
+
+/// Shortens `#rrggbb` and `#rrggbbaa` colors whose digits come in pairs, e.g. `#ff0000` to `#f00`. Other colors,
+/// including named ones and `rgb()`, are left unchanged.
+fn shorten_hex_color(color: &str) -> Option<String> {
+    let hex = color.strip_prefix('#')?.as_bytes();
+
+    if !matches!(hex.len(), 6 | 8)
+        || !hex.iter().all(u8::is_ascii_hexdigit)
+        || !hex
+            .chunks(2)
+            .all(|pair| pair[0].eq_ignore_ascii_case(&pair[1]))
+    {
+        return None;
+    }
+
+    let digits: String = hex.chunks(2).map(|pair| pair[0] as char).collect();
+    Some(format!("#{digits}"))
+}
+
+// Only values made of numbers and path commands are changed, as animated values may be arbitrary strings
+fn minify_value(value: &str) -> String {
+    let value = value.trim();
+
+    if let Some(color) = shorten_hex_color(value) {
+        color
+    } else if regex_is_match!(r"^[\d\s,.+\-eEMmLlHhVvCcSsQqTtAaZz]*$", value) {
+        let value = regex_replace_all!(r"\s*,\s*|\s+", value, |separator: &str| {
+            if separator.contains(',') { "," } else { " " }
+        });
+        replace_numbers(
+            &value,
+            regex!(r"[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?"),
+            minify_number,
+        )
+    } else {
+        value.into()
+    }
+}
+
+fn minify_value_list(values: &str) -> String {
+    values.split(';').map(minify_value).join(";")
+}
+
+fn minify_animation_attribute(mut attr: OwnedAttribute) -> OwnedAttribute {
+    match attr.name.local_name.as_str() {
+        VALUES_NAME | KEY_TIMES_NAME | KEY_SPLINES_NAME => {
+            attr.value = minify_value_list(&attr.value);
+        }
+        FROM_NAME | TO_NAME | BY_NAME | PATH_NAME => {
+            attr.value = minify_value(&attr.value);
+        }
+        _ => {}
+    }
+    attr
+}
+
+// Values of animated `href`s are id references, which may look like colors, e.g. `#aabbcc`
+fn animates_reference(attributes: &[OwnedAttribute]) -> bool {
+    find_attribute(attributes, ATTRIBUTE_NAME_NAME)
+        .is_some_and(|name| name == HREF_NAME || name.ends_with(":href"))
+}
+
+fn add_styled_properties(nodes: &[Node], properties: &mut HashSet<String>) {
+    for node in nodes {
+        match node {
+            Node::RegularNode {
+                node_type: RegularNodeType::Style,
+                children,
+                ..
+            } => {
+                for child in children {
+                    if let Node::ChildlessNode {
+                        node_type: ChildlessNodeType::Text(text, ..),
+                    } = child
+                    {
+                        let text = regex_replace_all!(r"(?s)/\*.*?\*/", text, "");
+                        properties.extend(
+                            regex!(r"([\w-]+)\s*:")
+                                .captures_iter(&text)
+                                .filter_map(|captures| captures.get(1))
+                                .map(|name| name.as_str().to_string()),
+                        );
+                    }
+                }
+            }
+            Node::RegularNode { children, .. } => add_styled_properties(children, properties),
+            Node::ChildlessNode { .. } => {}
+        }
+    }
+}
+
+// `<animate>` which sets the attribute of its parent to the value the parent already has. A property set by
+// a stylesheet overrides the attribute, so the animation may still change the rendered value. Property names are
+// read from declarations like `fill: red` in stylesheets; selectors such as `a:hover` only make this stricter.
+fn is_noop_animation(
+    animation: &Node,
+    target_attributes: &[OwnedAttribute],
+    styled_properties: &HashSet<String>,
+) -> bool {
+    if let Node::RegularNode {
+        node_type: RegularNodeType::Animate,
+        attributes,
+        ..
+    } = animation
+        && let Some(attribute_name) = find_attribute(attributes, ATTRIBUTE_NAME_NAME)
+        && let Some(from) = find_attribute(attributes, FROM_NAME)
+        && let Some(to) = find_attribute(attributes, TO_NAME)
+    {
+        from == to
+            && [VALUES_NAME, BY_NAME, ID_NAME, HREF_NAME]
+                .iter()
+                .all(|name| find_attribute(attributes, name).is_none())
+            && [ADDITIVE_NAME, ACCUMULATE_NAME]
+                .iter()
+                .all(|name| find_attribute(attributes, name).is_none_or(|value| value != "sum"))
+            && find_attribute(target_attributes, STYLE_NAME).is_none()
+            && !styled_properties.contains(attribute_name)
+            && find_attribute(target_attributes, attribute_name)
+                .is_some_and(|value| minify_value(value) == *from)
+    } else {
+        false
+    }
+}
+
+fn minify_animations_in_node(node: Node, styled_properties: &HashSet<String>) -> Node {
+    match node {
+        Node::RegularNode {
+            node_type:
+                node_type @ (RegularNodeType::Animate
+                | RegularNodeType::AnimateMotion
+                | RegularNodeType::AnimateTransform
+                | RegularNodeType::Set),
+            namespace,
+            attributes,
+            children,
+        } => Node::RegularNode {
+            node_type,
+            namespace,
+            attributes: if animates_reference(&attributes) {
+                attributes
+            } else {
+                attributes.map_to_vec(minify_animation_attribute)
+            },
+            children: minify_animations_in_nodes(children, styled_properties),
+        },
+        Node::RegularNode {
+            node_type,
+            namespace,
+            attributes,
+            children,
+        } => {
+            let children = minify_animations_in_nodes(children, styled_properties)
+                .filter_to_vec(|child| !is_noop_animation(child, &attributes, styled_properties));
+
+            Node::RegularNode {
+                node_type,
+                namespace,
+                attributes,
+                children,
+            }
+        }
+        other => other,
+    }
+}
+
+fn minify_animations_in_nodes(nodes: Vec<Node>, styled_properties: &HashSet<String>) -> Vec<Node> {
+    nodes.map_to_vec(|node| minify_animations_in_node(node, styled_properties))
+}
+
+pub(crate) fn minify_animations(nodes: Vec<Node>) -> Vec<Node> {
+    let mut styled_properties = HashSet::new();
+    add_styled_properties(&nodes, &mut styled_properties);
+
+    minify_animations_in_nodes(nodes, &styled_properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizations::common::test::test_optimize;
+    use crate::parser::Parser;
+    use crate::writer::SVGWriter;
+
+    test_optimize!(
+        test_minify_animations,
+        minify_animations,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10" fill="#ff0000">
+        <animate attributeName="width" values="10.0; 20.50 ;0010" keyTimes="0; 0.50; 1.0" keySplines="0.420 0 0.580 1.0 ; 0 0 1 1" calcMode="spline" dur="1s"/>
+        <animate attributeName="fill" from="#ff0000" to="#00FF00" dur="1s"/>
+        <animate attributeName="stroke" values="#ff000080;#ff000088;#f00;red" dur="1s"/>
+        <set attributeName="visibility" to=" hidden " begin="1.50s"/>
+        <animateTransform attributeName="transform" type="rotate" from="0 , 50 50" to="360.0 50.000 50" dur="1s"/>
+        <animateMotion path="M 0.5 0.0 L 100.00 -0.50 Z" dur="1s"/>
+        </rect>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10" fill="#ff0000">
+        <animate attributeName="width" values="10;20.5;10" keyTimes="0;.5;1" keySplines=".42 0 .58 1;0 0 1 1" calcMode="spline" dur="1s"/>
+        <animate attributeName="fill" from="#f00" to="#0F0" dur="1s"/>
+        <animate attributeName="stroke" values="#ff000080;#f008;#f00;red" dur="1s"/>
+        <set attributeName="visibility" to="hidden" begin="1.50s"/>
+        <animateTransform attributeName="transform" type="rotate" from="0,50 50" to="360 50 50" dur="1s"/>
+        <animateMotion path="M .5 0 L 100 -.5 Z" dur="1s"/>
+        </rect>
+        </svg>
+        "##
+    );
+
+    test_optimize!(
+        test_minify_animations_remove_noop,
+        minify_animations,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10" opacity="1.0"><animate attributeName="opacity" from="1" to="1.00" dur="1s"/><animate attributeName="width" from="10" to="10" dur="1s"/></rect>
+        <rect width="10" height="10"><animate attributeName="width" from="20" to="20" dur="1s"/><animate attributeName="width" from="10" to="10" additive="sum" dur="1s"/></rect>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10" opacity="1.0"/>
+        <rect width="10" height="10"><animate attributeName="width" from="20" to="20" dur="1s"/><animate attributeName="width" from="10" to="10" additive="sum" dur="1s"/></rect>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_minify_animations_adjacent_numbers,
+        minify_animations,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10"><animate attributeName="x" values="1.0.5;2" dur="1s"/><animateMotion path="M0.0.5L1 1" dur="1s"/></rect>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10"><animate attributeName="x" values="1 .5;2" dur="1s"/><animateMotion path="M0 .5L1 1" dur="1s"/></rect>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_minify_animations_keep_references_and_styled,
+        minify_animations,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <style>rect { opacity: .5 }</style>
+        <use href="#aaa"><set attributeName="href" to="#aabbcc" begin="1s"/></use>
+        <rect width="10" height="10" opacity="1"><animate attributeName="opacity" from="1" to="1" dur="1s"/></rect>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <style>rect { opacity: .5 }</style>
+        <use href="#aaa"><set attributeName="href" to="#aabbcc" begin="1s"/></use>
+        <rect width="10" height="10" opacity="1"><animate attributeName="opacity" from="1" to="1" dur="1s"/></rect>
+        </svg>
+        "##
+    );
+
+    test_optimize!(
+        test_minify_animations_styled_properties,
+        minify_animations,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <style>/* fill: red */ rect:hover { fill-opacity: .5; stroke:blue }</style>
+        <rect width="10" height="10" fill="red" stroke="red"><animate attributeName="fill" from="red" to="red" dur="1s"/><animate attributeName="stroke" from="red" to="red" dur="1s"/></rect>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <style>/* fill: red */ rect:hover { fill-opacity: .5; stroke:blue }</style>
+        <rect width="10" height="10" fill="red" stroke="red"><animate attributeName="stroke" from="red" to="red" dur="1s"/></rect>
+        </svg>
+        "#
+    );
+}
@@ -0,0 +1,36 @@
+use super::registry::Kind;
+
+pub(crate) const DEFAULT_PRECISION: usize = 3;
+
+/// Set of optimizations enabled by default, along with their parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Preset {
+    /// Optimizations which do not change ids or the structure of the document.
+    Safe,
+    /// All optimizations except the ones which are disabled by default.
+    #[default]
+    Default,
+    /// All optimizations and lossy ones, with precision 2.
+    Aggressive,
+    /// All optimizations and lossy ones, with precision 1 suited for small icons.
+    Icons,
+}
+
+impl Preset {
+    /// Checks if optimizations of the kind are enabled by the preset.
+    pub(crate) fn enables(self, kind: Kind) -> bool {
+        match self {
+            Self::Safe => kind == Kind::Safe,
+            Self::Default => matches!(kind, Kind::Safe | Kind::Structural),
+            Self::Aggressive | Self::Icons => kind != Kind::OptIn,
+        }
+    }
+
+    pub(crate) fn precision(self) -> usize {
+        match self {
+            Self::Safe | Self::Default => DEFAULT_PRECISION,
+            Self::Aggressive => 2,
+            Self::Icons => 1,
+        }
+    }
+}
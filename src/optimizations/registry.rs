@@ -0,0 +1,179 @@
+use crate::document::Document;
+use crate::node::Node;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+/// Optimization which can be applied to a [`Document`].
+///
+/// The built-in optimizations implement it as well, and custom ones can be added to a [`Config`](crate::Config) with
+/// [`Config::register`](crate::Config::register), after which they are ordered, reported and repeated by multipass
+/// the same way.
+pub trait Optimization: Send + Sync {
+    /// Unique name of the optimization, used by [`Config::passes`](crate::Config::passes) and in reports.
+    fn name(&self) -> &'static str;
+
+    /// Short description of what the optimization does.
+    fn description(&self) -> &'static str;
+
+    /// Checks if the optimization may change how the document is rendered. Lossy optimizations are disabled by default
+    /// and enabled by the aggressive presets.
+    fn lossy(&self) -> bool {
+        false
+    }
+
+    /// Parameters the optimization is applied with. Parameters which are not set and empty lists are left out.
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    /// Applies the optimization to the document.
+    fn apply(&self, document: &mut Document);
+}
+
+/// Value of a parameter of an optimization.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum ParameterValue {
+    /// A number, e.g. a precision or a number of bytes.
+    Number(usize),
+    /// A list of names, e.g. of kept elements or namespace URIs.
+    List(Vec<String>),
+}
+
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::List(names) => write!(f, "{}", names.join(",")),
+        }
+    }
+}
+
+/// Parameter an optimization is applied with.
+///
+/// Parameters of the built-in optimizations are named like their command line options, configuration file keys and
+/// [`Config`](crate::Config) methods, e.g. `min_use_saving` for `--min-use-saving`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub value: ParameterValue,
+}
+
+impl Parameter {
+    /// Returns the parameter if the number is set.
+    pub(crate) fn number(name: &'static str, number: Option<usize>) -> Option<Self> {
+        number.map(|number| Self {
+            name,
+            value: ParameterValue::Number(number),
+        })
+    }
+
+    /// Returns the parameter if the list is not empty.
+    pub(crate) fn list(name: &'static str, names: Vec<String>) -> Option<Self> {
+        (!names.is_empty()).then_some(Self {
+            name,
+            value: ParameterValue::List(names),
+        })
+    }
+}
+
+/// Typed parameters of a built-in optimization, declared next to the optimization.
+pub(crate) trait PassParameters:
+    Clone + fmt::Debug + Default + PartialEq + Send + Sync + 'static
+{
+    /// Fills in the parameters which default to the precision of lossy optimizations.
+    #[must_use]
+    fn with_precision(self, _precision: usize) -> Self {
+        self
+    }
+
+    /// Returns the parameters which are set.
+    fn parameters(&self) -> Vec<Parameter>;
+}
+
+impl PassParameters for () {
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+}
+
+/// Decides which presets enable an optimization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// Enabled by default, including by the safe preset.
+    Safe,
+    /// Enabled by default, except by the safe preset, as it changes ids or the structure of the document, which other
+    /// tools or scripts may rely on.
+    Structural,
+    /// Disabled by default.
+    OptIn,
+    /// Disabled by default, and enabled by the aggressive presets.
+    Lossy,
+}
+
+pub(crate) struct Builtin<P> {
+    pub(crate) name: &'static str,
+    pub(crate) description: &'static str,
+    pub(crate) kind: Kind,
+    pub(crate) parameters: P,
+    pub(crate) optimize: fn(Vec<Node>, &P) -> Vec<Node>,
+}
+
+impl<P: PassParameters> Optimization for Builtin<P> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn lossy(&self) -> bool {
+        self.kind == Kind::Lossy
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        self.parameters.parameters()
+    }
+
+    fn apply(&self, document: &mut Document) {
+        document.map_nodes(|nodes| (self.optimize)(nodes, &self.parameters));
+    }
+}
+
+impl fmt::Debug for dyn Optimization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Optimization")
+            .field("name", &self.name())
+            .field("lossy", &self.lossy())
+            .field("parameters", &self.parameters())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Optimization in the registry, along with what decides if it is applied.
+#[derive(Clone, Debug)]
+pub(crate) struct Registered {
+    pub(crate) optimization: Arc<dyn Optimization>,
+    pub(crate) kind: Kind,
+    /// Set if the optimization was explicitly enabled or disabled.
+    pub(crate) enabled: Option<bool>,
+}
+
+impl Registered {
+    pub(crate) fn name(&self) -> &'static str {
+        self.optimization.name()
+    }
+}
+
+// Optimizations are compared by what they tell about themselves, as their state is not visible otherwise
+impl PartialEq for Registered {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+            && self.optimization.lossy() == other.optimization.lossy()
+            && self.optimization.parameters() == other.optimization.parameters()
+            && self.kind == other.kind
+            && self.enabled == other.enabled
+    }
+}
@@ -1,13 +1,74 @@
 use super::common::iter::EasyIter;
+use super::registry::{Parameter, PassParameters};
 use crate::node::{Node, RegularNodeType};
 
-fn remove_descriptions_from_node(node: Node) -> Option<Node> {
+/// Element removed by `--remove-descriptions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptionElement {
+    /// `<title>`, shown as a tooltip and read by screen readers.
+    Title,
+    /// `<desc>`.
+    Desc,
+    /// `<metadata>`.
+    Metadata,
+}
+
+impl DescriptionElement {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Desc => "desc",
+            Self::Metadata => "metadata",
+        }
+    }
+
+    fn of(node_type: &RegularNodeType) -> Option<Self> {
+        match node_type {
+            RegularNodeType::Title => Some(Self::Title),
+            RegularNodeType::Description => Some(Self::Desc),
+            RegularNodeType::Metadata => Some(Self::Metadata),
+            _ => None,
+        }
+    }
+}
+
+/// Elements kept by `--remove-descriptions`, e.g. titles needed for accessibility.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeptDescriptions {
+    keep_description: Vec<DescriptionElement>,
+}
+
+impl KeptDescriptions {
+    /// Keeps the elements of the given kind.
+    #[must_use]
+    pub fn keep_description(mut self, element: DescriptionElement) -> Self {
+        self.keep_description.push(element);
+        self
+    }
+
+    fn is_removed(&self, node_type: &RegularNodeType) -> bool {
+        DescriptionElement::of(node_type)
+            .is_some_and(|element| !self.keep_description.contains(&element))
+    }
+}
+
+impl PassParameters for KeptDescriptions {
+    fn parameters(&self) -> Vec<Parameter> {
+        let names = self
+            .keep_description
+            .iter()
+            .map(|element| element.name().to_string())
+            .collect();
+
+        Parameter::list("keep_description", names)
+            .into_iter()
+            .collect()
+    }
+}
+
+fn remove_descriptions_from_node(node: Node, kept_descriptions: &KeptDescriptions) -> Option<Node> {
     match node {
-        Node::RegularNode {
-            node_type:
-                RegularNodeType::Description | RegularNodeType::Metadata | RegularNodeType::Title,
-            ..
-        } => None,
+        Node::RegularNode { node_type, .. } if kept_descriptions.is_removed(&node_type) => None,
         Node::RegularNode {
             node_type,
             namespace,
@@ -17,14 +78,17 @@ fn remove_descriptions_from_node(node: Node) -> Option<Node> {
             node_type,
             namespace,
             attributes,
-            children: remove_descriptions(children),
+            children: remove_descriptions(children, kept_descriptions),
         }),
         other => Some(other),
     }
 }
 
-pub(crate) fn remove_descriptions(nodes: Vec<Node>) -> Vec<Node> {
-    nodes.filter_map_to_vec(remove_descriptions_from_node)
+pub(crate) fn remove_descriptions(
+    nodes: Vec<Node>,
+    kept_descriptions: &KeptDescriptions,
+) -> Vec<Node> {
+    nodes.filter_map_to_vec(|node| remove_descriptions_from_node(node, kept_descriptions))
 }
 
 #[cfg(test)]
@@ -34,9 +98,20 @@ mod tests {
     use crate::parser::Parser;
     use crate::writer::SVGWriter;
 
+    fn test_remove_all_descriptions(nodes: Vec<Node>) -> Vec<Node> {
+        remove_descriptions(nodes, &KeptDescriptions::default())
+    }
+
+    fn test_keep_titles(nodes: Vec<Node>) -> Vec<Node> {
+        remove_descriptions(
+            nodes,
+            &KeptDescriptions::default().keep_description(DescriptionElement::Title),
+        )
+    }
+
     test_optimize!(
         test_remove_descriptions,
-        remove_descriptions,
+        test_remove_all_descriptions,
         r#"
 <desc> You cannot describe me into nonexistence! </desc>
         <svg xmlns="http://www.w3.org/2000/svg">
@@ -56,4 +131,15 @@ mod tests {
         </svg>
         "#
     );
+
+    test_optimize!(
+        test_remove_descriptions_keep_titles,
+        test_keep_titles,
+        r#"<svg xmlns="http://www.w3.org/2000/svg">
+        <title>Icon</title><desc>Drawn by hand</desc><metadata/>
+        </svg>"#,
+        r#"<svg xmlns="http://www.w3.org/2000/svg">
+        <title>Icon</title>
+        </svg>"#
+    );
 }
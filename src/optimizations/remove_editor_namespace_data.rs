@@ -1,48 +1,130 @@
+use super::common::{id_usage::find_attribute, iter::EasyIter};
+use super::registry::{Parameter, PassParameters};
+use crate::node::{Node, NodeNamespace, RegularNodeType};
 use xml::attribute::OwnedAttribute;
 
-use super::common::iter::EasyIter;
-use crate::node::{Node, NodeNamespace};
+const REQUIRED_EXTENSIONS_NAME: &str = "requiredExtensions";
 
-const ALLOWED_NAMESPACES: [&str; 3] = ["", "xlink", "xml"];
+const EDITOR_NAMESPACES: [&str; 17] = [
+    // Inkscape and Sodipodi
+    "http://www.inkscape.org/namespaces/inkscape",
+    "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+    "http://inkscape.sourceforge.net/DTD/sodipodi-0.dtd",
+    // Adobe Illustrator
+    "http://ns.adobe.com/AdobeIllustrator/10.0/",
+    "http://ns.adobe.com/AdobeSVGViewerExtensions/3.0/",
+    "http://ns.adobe.com/Extensibility/1.0/",
+    "http://ns.adobe.com/Flows/1.0/",
+    "http://ns.adobe.com/GenericCustomNamespace/1.0/",
+    "http://ns.adobe.com/Graphs/1.0/",
+    "http://ns.adobe.com/ImageReplacement/1.0/",
+    "http://ns.adobe.com/SaveForWeb/1.0/",
+    "http://ns.adobe.com/Variables/1.0/",
+    "http://ns.adobe.com/XPath/1.0/",
+    // Sketch
+    "http://www.bohemiancoding.com/sketch/ns",
+    // Figma
+    "http://www.figma.com/figma/ns",
+    // CorelDRAW
+    "http://www.corel.com/coreldraw/odm/2003",
+    "http://www.corel.com/coreldraw/svg",
+];
 
-fn remove_editor_namespace_data_from_namespace(mut namespace: NodeNamespace) -> NodeNamespace {
+/// Namespaces handled by `--remove-editor-namespace-data` in addition to the known editor namespaces.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorNamespaces {
+    keep_namespace: Vec<String>,
+    remove_namespace: Vec<String>,
+}
+
+impl EditorNamespaces {
+    /// Keeps the namespace, even if it belongs to a known editor.
+    #[must_use]
+    pub fn keep_namespace(mut self, uri: impl Into<String>) -> Self {
+        self.keep_namespace.push(uri.into());
+        self
+    }
+
+    /// Removes the namespace in addition to the known editor namespaces.
+    #[must_use]
+    pub fn remove_namespace(mut self, uri: impl Into<String>) -> Self {
+        self.remove_namespace.push(uri.into());
+        self
+    }
+
+    fn is_editor_namespace(&self, uri: &str) -> bool {
+        !self.keep_namespace.iter().any(|kept| kept == uri)
+            && (EDITOR_NAMESPACES.contains(&uri)
+                || self.remove_namespace.iter().any(|removed| removed == uri))
+    }
+
+    fn is_editor_data(&self, uri: Option<&String>) -> bool {
+        uri.is_some_and(|uri| self.is_editor_namespace(uri))
+    }
+}
+
+impl PassParameters for EditorNamespaces {
+    fn parameters(&self) -> Vec<Parameter> {
+        [
+            Parameter::list("keep_namespace", self.keep_namespace.clone()),
+            Parameter::list("remove_namespace", self.remove_namespace.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+fn remove_editor_namespace_data_from_namespace(
+    mut namespace: NodeNamespace,
+    editor_namespaces: &EditorNamespaces,
+) -> NodeNamespace {
     namespace
         .element_namespace
         .0
-        .retain(|key, _| ALLOWED_NAMESPACES.contains(&key.as_str()));
-    NodeNamespace {
-        parent_namespace: namespace.parent_namespace,
-        prefix: namespace.prefix,
-        element_namespace: namespace.element_namespace,
-    }
+        .retain(|_, uri| !editor_namespaces.is_editor_namespace(uri));
+    namespace
 }
 
-fn has_editor_namespace_data(attr: &OwnedAttribute) -> bool {
-    attr.name
-        .prefix
-        .as_ref()
-        .is_none_or(|prefix| ALLOWED_NAMESPACES.contains(&prefix.as_str()))
+// Adobe Illustrator stores its private data in `<foreignObject>`s only rendered by itself, e.g. `<i:pgfRef>`
+fn is_editor_only_content(
+    node_type: &RegularNodeType,
+    attributes: &[OwnedAttribute],
+    editor_namespaces: &EditorNamespaces,
+) -> bool {
+    *node_type == RegularNodeType::ForeignObject
+        && editor_namespaces.is_editor_data(find_attribute(attributes, REQUIRED_EXTENSIONS_NAME))
 }
 
-fn remove_editor_namespace_data_from_node(node: Node) -> Option<Node> {
+fn remove_editor_namespace_data_from_node(
+    node: Node,
+    editor_namespaces: &EditorNamespaces,
+) -> Option<Node> {
     match node {
         Node::RegularNode {
             node_type,
             namespace,
             attributes,
             children,
-        } => namespace.prefix.is_none().then_some(Node::RegularNode {
+        } => (!editor_namespaces.is_editor_data(namespace.parent_namespace.as_ref())
+            && !is_editor_only_content(&node_type, &attributes, editor_namespaces))
+        .then(|| Node::RegularNode {
             node_type,
-            namespace: remove_editor_namespace_data_from_namespace(namespace),
-            attributes: attributes.filter_to_vec(has_editor_namespace_data),
-            children: children.filter_map_to_vec(remove_editor_namespace_data_from_node),
+            namespace: remove_editor_namespace_data_from_namespace(namespace, editor_namespaces),
+            attributes: attributes.filter_to_vec(|attr| {
+                !editor_namespaces.is_editor_data(attr.name.namespace.as_ref())
+            }),
+            children: remove_editor_namespace_data(children, editor_namespaces),
         }),
         other => Some(other),
     }
 }
 
-pub(crate) fn remove_editor_namespace_data(nodes: Vec<Node>) -> Vec<Node> {
-    nodes.filter_map_to_vec(remove_editor_namespace_data_from_node)
+pub(crate) fn remove_editor_namespace_data(
+    nodes: Vec<Node>,
+    editor_namespaces: &EditorNamespaces,
+) -> Vec<Node> {
+    nodes.filter_map_to_vec(|node| remove_editor_namespace_data_from_node(node, editor_namespaces))
 }
 
 #[cfg(test)]
@@ -52,9 +134,21 @@ mod tests {
     use crate::parser::Parser;
     use crate::writer::SVGWriter;
 
+    fn test_remove_editor_data(nodes: Vec<Node>) -> Vec<Node> {
+        remove_editor_namespace_data(nodes, &EditorNamespaces::default())
+    }
+
+    fn test_remove_custom_editor_data(nodes: Vec<Node>) -> Vec<Node> {
+        let editor_namespaces = EditorNamespaces {
+            keep_namespace: vec!["http://www.inkscape.org/namespaces/inkscape".into()],
+            remove_namespace: vec!["http://example.com/editor".into()],
+        };
+        remove_editor_namespace_data(nodes, &editor_namespaces)
+    }
+
     test_optimize!(
         test_remove_editor_namespace_data,
-        remove_editor_namespace_data,
+        test_remove_editor_data,
         r##"
         <svg xmlns="http://www.w3.org/2000/svg" xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">
         <marker inkscape:stockid="Arrow2Lend" orient="auto" refY="0.0" refX="0.0" id="Arrow2Lend" style="overflow:visible;"/><sodipodi:namedview
@@ -85,4 +179,51 @@ mod tests {
         </svg>
         "#
     );
+
+    test_optimize!(
+        test_remove_editor_namespace_data_keep_foreign_content,
+        test_remove_editor_data,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" xmlns:m="http://www.w3.org/1998/Math/MathML">
+        <foreignObject inkscape:label="formula" width="10" height="10"><m:math><m:mi>x</m:mi></m:math></foreignObject>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:m="http://www.w3.org/1998/Math/MathML">
+        <foreignObject width="10" height="10"><m:math><m:mi>x</m:mi></m:math></foreignObject>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_remove_editor_namespace_data_illustrator,
+        test_remove_editor_data,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:x="http://ns.adobe.com/Extensibility/1.0/" xmlns:i="http://ns.adobe.com/AdobeIllustrator/10.0/" xmlns:xlink="http://www.w3.org/1999/xlink">
+        <switch><foreignObject requiredExtensions="http://ns.adobe.com/AdobeIllustrator/10.0/" x="0" y="0" width="1" height="1"><i:pgfRef xlink:href="#adobe_illustrator_pgf"/></foreignObject><g i:extraneous="self"><rect width="10" height="10"/></g></switch>
+        <i:pgf id="adobe_illustrator_pgf">eJzs...</i:pgf>
+        </svg>
+        "##,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+        <switch><g><rect width="10" height="10"/></g></switch>
+        
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_remove_editor_namespace_data_custom,
+        test_remove_custom_editor_data,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" xmlns:editor="http://example.com/editor" xmlns:other="http://example.com/other">
+        <g inkscape:label="Layer" editor:layer="1" other:data="2"/>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" xmlns:other="http://example.com/other">
+        <g inkscape:label="Layer" other:data="2"/>
+        </svg>
+        "#
+    );
 }
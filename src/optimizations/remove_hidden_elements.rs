@@ -1,5 +1,10 @@
 #[allow(clippy::wildcard_imports)]
-use super::common::{constants::*, iter::EasyIter, unit::find_and_convert_to_px};
+use super::common::{
+    constants::*,
+    id_usage::{IdUsage, find_attribute},
+    iter::EasyIter,
+    unit::find_and_convert_to_px,
+};
 use crate::node::{Node, RegularNodeType};
 use xml::attribute::OwnedAttribute;
 
@@ -86,16 +91,67 @@ fn remove_empty_data(node: &Node) -> bool {
     }
 }
 
-fn should_remove(node: &Node) -> bool {
-    remove_no_display(node)
-        || remove_circle(node)
-        || remove_ellipse(node)
-        || remove_zero_dimensions(node)
-        || remove_empty_data(node)
+// Attributes which can make an element hidden, including the ones checked by rules above
+const HIDING_ATTRIBUTES: [&str; 10] = [
+    DISPLAY_NAME,
+    OPACITY_NAME,
+    VISIBILITY_NAME,
+    R_NAME,
+    RX_NAME,
+    RY_NAME,
+    WIDTH_NAME,
+    HEIGHT_NAME,
+    PATH_DATA_NAME,
+    POINTS_NAME,
+];
+
+fn is_hiding_attribute(name: &str) -> bool {
+    HIDING_ATTRIBUTES.contains(&name)
 }
 
-fn remove_hidden_elements_from_node(node: Node) -> Option<Node> {
-    (!should_remove(&node)).then_some(match node {
+/// Checks whether an attribute which may hide the node is animated by a child or an element targeting its id.
+fn is_animated(node: &Node, id_usage: &IdUsage) -> bool {
+    match node {
+        Node::RegularNode {
+            attributes,
+            children,
+            ..
+        } => {
+            let is_animated_by_child = children.iter().any(|child| {
+                matches!(
+                    child,
+                    Node::RegularNode {
+                        node_type: RegularNodeType::Animate | RegularNodeType::Set,
+                        attributes,
+                        ..
+                    } if find_attribute(attributes, ATTRIBUTE_NAME_NAME)
+                        .is_some_and(|name| is_hiding_attribute(name))
+                )
+            });
+            let is_animated_by_reference = find_attribute(attributes, ID_NAME).is_some_and(|id| {
+                id_usage
+                    .animated_attributes(id)
+                    .iter()
+                    .any(|name| is_hiding_attribute(name))
+            });
+
+            is_animated_by_child || is_animated_by_reference
+        }
+        _ => false,
+    }
+}
+
+fn should_remove(node: &Node, id_usage: &IdUsage) -> bool {
+    !is_animated(node, id_usage)
+        && (remove_no_display(node)
+            || remove_circle(node)
+            || remove_ellipse(node)
+            || remove_zero_dimensions(node)
+            || remove_empty_data(node))
+}
+
+fn remove_hidden_elements_from_node(node: Node, id_usage: &IdUsage) -> Option<Node> {
+    (!should_remove(&node, id_usage)).then_some(match node {
         Node::RegularNode {
             node_type,
             namespace,
@@ -105,14 +161,19 @@ fn remove_hidden_elements_from_node(node: Node) -> Option<Node> {
             node_type,
             namespace,
             attributes,
-            children: remove_hidden_elements(children),
+            children: remove_hidden_elements_from_nodes(children, id_usage),
         },
         other => other,
     })
 }
 
+fn remove_hidden_elements_from_nodes(nodes: Vec<Node>, id_usage: &IdUsage) -> Vec<Node> {
+    nodes.filter_map_to_vec(|node| remove_hidden_elements_from_node(node, id_usage))
+}
+
 pub(crate) fn remove_hidden_elements(nodes: Vec<Node>) -> Vec<Node> {
-    nodes.filter_map_to_vec(remove_hidden_elements_from_node)
+    let id_usage = IdUsage::new(&nodes);
+    remove_hidden_elements_from_nodes(nodes, &id_usage)
 }
 
 #[cfg(test)]
@@ -238,4 +299,32 @@ mod tests {
         </svg>
         "#
     );
+
+    test_optimize!(
+        test_remove_hidden_keep_animated,
+        remove_hidden_elements,
+        r#"
+        <svg viewBox="0 0 200 100" xmlns="http://www.w3.org/2000/svg"><rect width="10" height="10" opacity="0"><set attributeName="opacity" to="1" begin="1s"/></rect><circle cx="100" cy="50"><animate attributeName="r" from="0" to="10" dur="1s"/></circle><rect width="10" height="10" display="none"><animate attributeName="x" from="0" to="10" dur="1s"/></rect>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 100"><rect width="10" height="10" opacity="0"><set attributeName="opacity" to="1" begin="1s"/></rect><circle cx="100" cy="50"><animate attributeName="r" from="0" to="10" dur="1s"/></circle>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_remove_hidden_keep_animated_by_reference,
+        remove_hidden_elements,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg"><rect id="a" width="10" height="10" opacity="0"/><rect id="b" width="10" height="10" opacity="0"/><rect id="c" width="10" height="10" opacity="0"/>
+        <animate href="#a" attributeName="opacity" from="0" to="1" dur="1s"/><set href="#b" attributeName="x" to="1"/>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg"><rect id="a" width="10" height="10" opacity="0"/>
+        <animate href="#a" attributeName="opacity" from="0" to="1" dur="1s"/><set href="#b" attributeName="x" to="1"/>
+        </svg>
+        "##
+    );
 }
@@ -1,17 +1,27 @@
-use super::common::{constants::ID_NAME, id_usage::make_id_usage_map, iter::EasyIter};
+use super::common::{
+    constants::ID_NAME,
+    id_usage::{IdUsage, NodeLocation},
+    iter::EasyIter,
+};
 use crate::node::{Node, RegularNodeType};
-use std::collections::BTreeMap;
 
-fn is_used(node: &Node, id_usage_map: &BTreeMap<String, bool>) -> bool {
+fn child_location(location: &[usize], index: usize) -> NodeLocation {
+    let mut child_location = location.to_vec();
+    child_location.push(index);
+    child_location
+}
+
+// Definitions referenced only from their own subtree are not used
+fn is_used(node: &Node, location: &[usize], id_usage: &IdUsage) -> bool {
     match node {
-        Node::RegularNode { attributes, .. } => attributes
-            .iter()
-            .any(|attr| attr.name.local_name == ID_NAME && id_usage_map[&attr.value]),
+        Node::RegularNode { attributes, .. } => attributes.iter().any(|attr| {
+            attr.name.local_name == ID_NAME && id_usage.is_used_outside(&attr.value, location)
+        }),
         _ => true,
     }
 }
 
-fn remove_unused_defs_for_node(node: Node, id_usage_map: &BTreeMap<String, bool>) -> Option<Node> {
+fn remove_unused_defs_for_node(node: Node, location: &[usize], id_usage: &IdUsage) -> Option<Node> {
     match node {
         Node::RegularNode {
             node_type: RegularNodeType::Defs,
@@ -19,7 +29,13 @@ fn remove_unused_defs_for_node(node: Node, id_usage_map: &BTreeMap<String, bool>
             attributes,
             children,
         } => {
-            let new_children = children.filter_to_vec(|child| is_used(child, id_usage_map));
+            let new_children: Vec<Node> = children
+                .into_iter()
+                .enumerate()
+                .filter(|(index, child)| {
+                    is_used(child, &child_location(location, *index), id_usage)
+                })
+                .map_to_vec(|(_, child)| child);
 
             match new_children.len() {
                 0 => None,
@@ -40,16 +56,28 @@ fn remove_unused_defs_for_node(node: Node, id_usage_map: &BTreeMap<String, bool>
             node_type,
             namespace,
             attributes,
-            children: children
-                .filter_map_to_vec(|node| remove_unused_defs_for_node(node, id_usage_map)),
+            children: remove_unused_defs_from_nodes(children, location, id_usage),
         }),
         other => Some(other),
     }
 }
 
+fn remove_unused_defs_from_nodes(
+    nodes: Vec<Node>,
+    location: &[usize],
+    id_usage: &IdUsage,
+) -> Vec<Node> {
+    nodes
+        .into_iter()
+        .enumerate()
+        .filter_map_to_vec(|(index, node)| {
+            remove_unused_defs_for_node(node, &child_location(location, index), id_usage)
+        })
+}
+
 pub(crate) fn remove_unused_defs(nodes: Vec<Node>) -> Vec<Node> {
-    let id_usage_map = make_id_usage_map(&nodes);
-    nodes.filter_map_to_vec(|node| remove_unused_defs_for_node(node, &id_usage_map))
+    let id_usage = IdUsage::new(&nodes);
+    remove_unused_defs_from_nodes(nodes, &[], &id_usage)
 }
 
 #[cfg(test)]
@@ -182,4 +210,21 @@ mod tests {
         </svg>
         "#
     );
+
+    test_optimize!(
+        test_remove_unused_defs_self_reference,
+        remove_unused_defs,
+        r##"
+        <svg viewBox="0 0 10 10" xmlns="http://www.w3.org/2000/svg">
+        <defs><circle id="myCircle" cx="0" cy="0" r="5"><animate attributeName="r" to="0" begin="myCircle.click"/></circle><circle id="otherCircle" cx="0" cy="0" r="5"/></defs>
+        <use x="5" y="5" href="#otherCircle"/>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <defs><circle id="otherCircle" cx="0" cy="0" r="5"/></defs>
+        <use x="5" y="5" href="#otherCircle"/>
+        </svg>
+        "##
+    );
 }
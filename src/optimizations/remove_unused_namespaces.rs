@@ -0,0 +1,116 @@
+use super::common::iter::EasyIter;
+use crate::node::{Node, RegularNodeType};
+use std::collections::BTreeSet;
+use xml::namespace::Namespace;
+
+// Default namespace and namespaces predefined by XML are never removed
+const RESERVED_PREFIXES: [&str; 3] = ["", "xml", "xmlns"];
+
+fn is_declared_here(prefix: &str, uri: &str, parent_namespace: &Namespace) -> bool {
+    parent_namespace.get(prefix) != Some(uri)
+}
+
+/// Returns the node with unused namespaces removed and the prefixes used in its subtree.
+///
+/// Inside `<foreignObject>`, namespaces declared by the foreign content itself are kept even if unused,
+/// as they may be referenced in ways unknown to SVG, e.g. in attribute values.
+fn remove_unused_namespaces_from_node(
+    node: Node,
+    parent_namespace: &Namespace,
+    is_foreign: bool,
+) -> (Node, BTreeSet<String>) {
+    match node {
+        Node::RegularNode {
+            node_type,
+            mut namespace,
+            attributes,
+            children,
+        } => {
+            let children_are_foreign = is_foreign || node_type == RegularNodeType::ForeignObject;
+            let mut used_prefixes = BTreeSet::new();
+
+            let children = children
+                .into_iter()
+                .map(|child| {
+                    let (child, child_prefixes) = remove_unused_namespaces_from_node(
+                        child,
+                        &namespace.element_namespace,
+                        children_are_foreign,
+                    );
+                    used_prefixes.extend(child_prefixes);
+                    child
+                })
+                .collect();
+
+            used_prefixes.extend(namespace.prefix.clone());
+            used_prefixes.extend(
+                attributes
+                    .iter()
+                    .filter_map(|attr| attr.name.prefix.clone()),
+            );
+
+            namespace.element_namespace.0.retain(|prefix, uri| {
+                RESERVED_PREFIXES.contains(&prefix.as_str())
+                    || used_prefixes.contains(prefix)
+                    || (is_foreign && is_declared_here(prefix, uri, parent_namespace))
+            });
+
+            (
+                Node::RegularNode {
+                    node_type,
+                    namespace,
+                    attributes,
+                    children,
+                },
+                used_prefixes,
+            )
+        }
+        other => (other, BTreeSet::new()),
+    }
+}
+
+pub(crate) fn remove_unused_namespaces(nodes: Vec<Node>) -> Vec<Node> {
+    nodes.map_to_vec(|node| remove_unused_namespaces_from_node(node, &Namespace::empty(), false).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizations::common::test::test_optimize;
+    use crate::parser::Parser;
+    use crate::writer::SVGWriter;
+
+    test_optimize!(
+        test_remove_unused_namespaces,
+        remove_unused_namespaces,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <metadata><dc:title>Title</dc:title></metadata>
+        <rect width="10" height="10"/>
+        </svg>
+        "#,
+        r#"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <metadata><dc:title>Title</dc:title></metadata>
+        <rect width="10" height="10"/>
+        </svg>
+        "#
+    );
+
+    test_optimize!(
+        test_remove_unused_namespaces_keep_used,
+        remove_unused_namespaces,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:m="http://www.w3.org/1998/Math/MathML" xmlns:unused="http://example.com/unused">
+        <use xlink:href="#a"/>
+        <foreignObject width="10" height="10"><m:math><m:mi>x</m:mi></m:math><div xmlns="http://www.w3.org/1999/xhtml" xmlns:x="http://example.com/x">Text</div></foreignObject>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg" xmlns:m="http://www.w3.org/1998/Math/MathML" xmlns:xlink="http://www.w3.org/1999/xlink">
+        <use xlink:href="#a"/>
+        <foreignObject width="10" height="10"><m:math><m:mi>x</m:mi></m:math><div xmlns="http://www.w3.org/1999/xhtml" xmlns:x="http://example.com/x">Text</div></foreignObject>
+        </svg>
+        "##
+    );
+}
@@ -1,16 +1,12 @@
-use super::common::{constants::ID_NAME, id_usage::make_id_usage_map, iter::EasyIter};
+use super::common::{constants::ID_NAME, id_usage::IdUsage, iter::EasyIter};
 use crate::node::Node;
-use std::collections::BTreeMap;
 use xml::attribute::OwnedAttribute;
 
-fn is_attribute_useless_id(
-    attribute: &OwnedAttribute,
-    id_usage_map: &BTreeMap<String, bool>,
-) -> bool {
-    attribute.name.local_name == ID_NAME && !id_usage_map[&attribute.value]
+fn is_attribute_useless_id(attribute: &OwnedAttribute, id_usage: &IdUsage) -> bool {
+    attribute.name.local_name == ID_NAME && !id_usage.is_used(&attribute.value)
 }
 
-fn remove_useless_ids_for_node(node: Node, id_usage_map: &BTreeMap<String, bool>) -> Node {
+fn remove_useless_ids_for_node(node: Node, id_usage: &IdUsage) -> Node {
     match node {
         Node::RegularNode {
             node_type,
@@ -21,16 +17,16 @@ fn remove_useless_ids_for_node(node: Node, id_usage_map: &BTreeMap<String, bool>
             node_type,
             namespace,
             attributes: attributes
-                .filter_to_vec(|attribute| !is_attribute_useless_id(attribute, id_usage_map)),
-            children: children.map_to_vec(|child| remove_useless_ids_for_node(child, id_usage_map)),
+                .filter_to_vec(|attribute| !is_attribute_useless_id(attribute, id_usage)),
+            children: children.map_to_vec(|child| remove_useless_ids_for_node(child, id_usage)),
         },
         other => other,
     }
 }
 
 pub(crate) fn remove_useless_ids(nodes: Vec<Node>) -> Vec<Node> {
-    let id_usage_map = make_id_usage_map(&nodes);
-    nodes.map_to_vec(|node| remove_useless_ids_for_node(node, &id_usage_map))
+    let id_usage = IdUsage::new(&nodes);
+    nodes.map_to_vec(|node| remove_useless_ids_for_node(node, &id_usage))
 }
 
 #[cfg(test)]
@@ -109,4 +105,23 @@ mod tests {
         </svg>
         "#
     );
+
+    test_optimize!(
+        test_remove_useless_ids_animation_references,
+        remove_useless_ids,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect id="first" width="10" height="10"><animate id="grow" attributeName="width" to="20" dur="1s"/></rect>
+        <rect id="second" width="10" height="10"/>
+        <animate href="#second" attributeName="x" to="20" begin="grow.end" dur="1s"/>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect width="10" height="10"><animate id="grow" attributeName="width" to="20" dur="1s"/></rect>
+        <rect id="second" width="10" height="10"/>
+        <animate href="#second" attributeName="x" to="20" begin="grow.end" dur="1s"/>
+        </svg>
+        "##
+    );
 }
@@ -1,15 +1,47 @@
-use super::common::{iter::EasyIter, unit::round_float};
+use super::common::{
+    iter::EasyIter,
+    unit::{replace_numbers, round_float},
+};
+use super::preset::DEFAULT_PRECISION;
+use super::registry::{Parameter, PassParameters};
 use crate::node::Node;
-use lazy_regex::regex_replace_all;
+use lazy_regex::regex;
 use xml::attribute::OwnedAttribute;
 
+/// Number of digits kept by `--round-floats`. Defaults to `--precision`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoordinatePrecision {
+    coordinate_precision: Option<usize>,
+}
+
+impl CoordinatePrecision {
+    /// Rounds numbers to the given number of decimal places.
+    #[must_use]
+    pub fn new(digits: usize) -> Self {
+        Self {
+            coordinate_precision: Some(digits),
+        }
+    }
+}
+
+impl PassParameters for CoordinatePrecision {
+    fn with_precision(self, precision: usize) -> Self {
+        Self {
+            coordinate_precision: self.coordinate_precision.or(Some(precision)),
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        Parameter::number("coordinate_precision", self.coordinate_precision)
+            .into_iter()
+            .collect()
+    }
+}
+
 fn round_floats_in_attribute(mut attr: OwnedAttribute, precision: usize) -> OwnedAttribute {
-    attr.value = regex_replace_all!(
-        r"[+-]?\d*\.\d+([Ee]\d+)?",
-        attr.value.as_str(),
-        |float: &str, _| round_float(float.parse::<f64>().unwrap(), precision)
-    )
-    .into_owned();
+    attr.value = replace_numbers(&attr.value, regex!(r"[+-]?\d*\.\d+([Ee]\d+)?"), |float| {
+        round_float(float.parse::<f64>().unwrap(), precision)
+    });
     attr
 }
 
@@ -30,7 +62,8 @@ fn round_floats_in_node(node: Node, precision: usize) -> Node {
     }
 }
 
-pub(crate) fn round_floats(nodes: Vec<Node>, precision: usize) -> Vec<Node> {
+pub(crate) fn round_floats(nodes: Vec<Node>, precision: &CoordinatePrecision) -> Vec<Node> {
+    let precision = precision.coordinate_precision.unwrap_or(DEFAULT_PRECISION);
     nodes.map_to_vec(|node| round_floats_in_node(node, precision))
 }
 
@@ -42,7 +75,7 @@ mod tests {
     use crate::writer::SVGWriter;
 
     fn test_round(nodes: Vec<Node>) -> Vec<Node> {
-        round_floats(nodes, 2)
+        round_floats(nodes, &CoordinatePrecision::new(2))
     }
 
     test_optimize!(
@@ -51,11 +84,13 @@ mod tests {
         r#"
         <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 -00.101 00100.12">
         <path d="M 10,30.157 A 20,20.123 0,0,1 50,3.1e1 A 20.301,20 0,0,1 90,30 Q 90,60 50,90 Q 10,60 1.9E1,30 z"/>
+        <animate attributeName="d" values="M0.001.5L1 1;M1.257.5L1 1" dur="1s"/>
         </svg>
         "#,
         r#"
         <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 -.1 100.12">
         <path d="M 10,30.16 A 20,20.12 0,0,1 50,31 A 20.3,20 0,0,1 90,30 Q 90,60 50,90 Q 10,60 19,30 z"/>
+        <animate attributeName="d" values="M0 .5L1 1;M1.26.5L1 1" dur="1s"/>
         </svg>
         "#
     );
@@ -1,17 +1,12 @@
 use super::common::{
-    id_generator::IdGenerator, id_usage::find_ids_for_subtree, iter::EasyIter,
+    id_generator::{IdGenerator, is_hex_color_prefix},
+    id_usage::find_ids_for_subtree,
+    iter::EasyIter,
     replace_ids::replace_ids,
 };
 use crate::node::Node;
-use itertools::Itertools;
 use std::collections::BTreeMap;
 
-fn is_hex_color_prefix(id: &str) -> bool {
-    id.chars()
-        .all(|char| "abcdefABCDEF0123456789".chars().contains(&char))
-        && id.len() <= 6
-}
-
 fn make_shorten_ids_map(nodes: &Vec<Node>) -> BTreeMap<String, String> {
     let ids = find_ids_for_subtree(nodes).filter_to_vec(|id| !is_hex_color_prefix(id));
 
@@ -197,4 +192,21 @@ mod tests {
         </svg>
         "##
     );
+
+    test_optimize!(
+        test_shorten_ids_animation_references,
+        shorten_ids,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect id="firstRect" width="10" height="10"><animate id="growAnimation" attributeName="width" to="20" dur="1s" begin="firstRect.click"/></rect>
+        <animate href="#firstRect" attributeName="x" to="20" begin="growAnimation.end+1s; 2s" dur="1s"/>
+        </svg>
+        "##,
+        r##"
+        <svg xmlns="http://www.w3.org/2000/svg">
+        <rect id="g" width="10" height="10"><animate id="h" attributeName="width" to="20" dur="1s" begin="g.click"/></rect>
+        <animate href="#g" attributeName="x" to="20" begin="h.end+1s; 2s" dur="1s"/>
+        </svg>
+        "##
+    );
 }
@@ -1,43 +1,290 @@
-use crate::optimizations::Optimizations;
-use crate::parser::Parser;
-use crate::writer::SVGWriter;
+use crate::config::Config;
+use crate::config_args::{ConfigArgs, ConfigFileArgs};
+use crate::config_file::{ConfigFile, Options, Value};
+use crate::linter::lint;
+use crate::node::Node;
+use crate::optimizations::ALL_OPTIMIZATIONS;
+use crate::report::{self, FileReport, PassReport, ReportFormat, ReportKind};
+use crate::walker::{OUTPUT_PREFIX, WalkOptions};
 use anyhow::{Error, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
 use rayon::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::ffi::OsString;
-use std::fs::File;
-use std::io::BufReader;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Path which stands for standard input when given as an input file and standard output when given as an output file.
+const STDIO_PATH: &str = "-";
+
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == STDIO_PATH
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+/// Writes the contents to a temporary file next to the path first and then renames it,
+/// so that the file at the path is never left partially written.
+///
+/// Permissions of the replaced file are kept.
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let temp_path = with_suffix(path, &format!(".{}.tmp", std::process::id()));
+
+    let result = fs::write(&temp_path, contents)
+        .and_then(|()| match fs::metadata(path) {
+            Ok(metadata) => fs::set_permissions(&temp_path, metadata.permissions()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error),
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+struct OptimizedFile {
+    /// Output to write to standard output after all files are optimized.
+    stdout_output: Option<Vec<u8>>,
+    report: FileReport,
+    /// Whether the file fails `--check`.
+    unoptimized: bool,
+}
 
 /// Program that optimizes the size of SVG files.
 ///
 /// By default, all optimizations that do not take parameters are enabled.
 #[derive(clap::Parser)]
 #[command(version)]
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct Optimizer {
-    /// Names of the files to optimize.
+    /// Names of the files to optimize. `-` reads from standard input. Directories are allowed with `--recursive`.
     #[arg(num_args = 1..)]
     file_names: Vec<PathBuf>,
 
-    /// Names of the output files.
+    /// Names of the output files. `-` writes to standard output.
     ///
-    /// If given, must have the same length as `file_names`. Default output file names are `opt_{original_filename}`.
+    /// If given, must have the same length as `file_names`. Default output file names are `opt_{original_filename}`,
+    /// or standard output for standard input.
     #[arg(short, long, num_args = 1..)]
     output_file_names: Vec<PathBuf>,
 
-    /// Disable all optimizations by default.
-    #[arg(short, long)]
-    disable_by_default: bool,
+    /// Write all outputs to standard output, in the order of input files.
+    #[arg(long, conflicts_with = "output_file_names")]
+    stdout: bool,
+
+    /// Directory to write the output files to, mirroring the structure of input directories.
+    #[arg(long, conflicts_with_all = ["output_file_names", "stdout"])]
+    output_dir: Option<PathBuf>,
+
+    /// Replace the input files with the output files.
+    #[arg(short, long, conflicts_with_all = ["output_file_names", "stdout", "output_dir"])]
+    in_place: bool,
+
+    /// Keep a copy of each replaced file as `{original_filename}.bak`.
+    #[arg(long, requires = "in_place")]
+    backup: bool,
+
+    /// Output the original file unchanged if the optimized one is not smaller. Enabled by default with `--in-place` and
+    /// `--check`.
+    #[arg(long, overrides_with = "no_skip_if_larger")]
+    skip_if_larger: bool,
+
+    /// Always output the optimized file, even if it is larger than the original.
+    #[arg(long)]
+    no_skip_if_larger: bool,
+
+    /// Do not write any files, but fail if any of the input files would change.
+    #[arg(long, conflicts_with_all = ["output_file_names", "stdout", "output_dir", "in_place"])]
+    check: bool,
+
+    /// With `--check`, only fail if a file would shrink by more than the given percentage.
+    #[arg(long, value_name = "PERCENT", requires = "check")]
+    check_threshold: Option<f64>,
+
+    /// Print original and optimized sizes of each file to standard error.
+    /// `--report=passes` also prints how many bytes each optimization saved.
+    #[arg(
+        long,
+        value_name = "KIND",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "files"
+    )]
+    report: Option<ReportKind>,
+
+    /// Format of the report. The JSON report also includes timings, warnings and errors, and implies `--report`.
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t)]
+    report_format: ReportFormat,
+
+    /// Configuration file to use instead of the closest `svg-optimizer.toml` in directories of the input files
+    /// or their ancestors. Options given on the command line take precedence over the configuration.
+    #[arg(long = "config", value_name = "PATH")]
+    config_path: Option<PathBuf>,
+
+    /// Report broken id references, duplicate ids and ids which cannot be shortened instead of optimizing.
+    #[arg(long)]
+    lint: bool,
+
+    /// Print names and descriptions of all optimizations, as accepted by `--passes`, instead of optimizing.
+    #[arg(long)]
+    list_passes: bool,
+
+    #[command(flatten)]
+    walk_options: WalkOptions,
 
     #[command(flatten)]
-    optimizations: Optimizations,
+    config_args: ConfigArgs,
+}
+
+/// Options which can be given in a configuration file, with the names of the command line options as keys. Options
+/// selecting input and output files, or what to do with them, can only be given on the command line.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct FileOptions {
+    skip_if_larger: Option<bool>,
+    #[serde(flatten)]
+    config_args: ConfigFileArgs,
+}
+
+// Files with the same name in different input directories would overwrite each other in `--output-dir`
+fn check_output_collisions(files: &[(PathBuf, Option<PathBuf>)]) -> Result<()> {
+    let mut inputs_by_output = HashMap::new();
+
+    for (input_path, output_path) in files {
+        if let Some(output_path) = output_path
+            && !is_stdio(output_path)
+            && let Some(other_input_path) = inputs_by_output.insert(output_path, input_path)
+        {
+            return Err(Error::msg(format!(
+                "{} and {} would both be written to {}",
+                other_input_path.display(),
+                input_path.display(),
+                output_path.display()
+            )));
+        }
+    }
+
+    Ok(())
 }
 
 impl Optimizer {
+    /// Optimizes files given on the command line.
+    pub(crate) fn run(args: impl IntoIterator<Item = impl Into<OsString> + Clone>) -> Result<()> {
+        let cli_matches = Self::command().get_matches_from(args);
+        Self::from_arg_matches(&cli_matches)?.optimize(&cli_matches)
+    }
+
+    /// Returns the path of the configuration file for the file, if there is one.
+    fn config_path(&self, input_path: &Path) -> Result<Option<PathBuf>> {
+        if let Some(config_path) = &self.config_path {
+            return Ok(Some(config_path.clone()));
+        }
+
+        let dir = if is_stdio(input_path) {
+            std::env::current_dir()?
+        } else {
+            let input_path = std::path::absolute(input_path)?;
+            input_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        };
+        Ok(ConfigFile::find(&dir))
+    }
+
+    /// Returns options combined from the configuration and the command line. Options given on the command line
+    /// are kept, while the configuration replaces the defaults of the others.
+    fn configured_optimizer(options: Options, cli_matches: &ArgMatches) -> Result<Self> {
+        let options: FileOptions = Value::Table(options).try_into()?;
+        if let Some(key) = options.config_args.unknown_keys().next() {
+            let is_option = Self::command()
+                .get_arguments()
+                .any(|arg| arg.get_long() == Some(key));
+            return Err(Error::msg(if is_option {
+                format!("Option `{key}` can only be given on the command line")
+            } else {
+                format!("Unknown option `{key}`")
+            }));
+        }
+
+        let is_given = |id: &str| cli_matches.value_source(id) == Some(ValueSource::CommandLine);
+        let mut optimizer = Self::from_arg_matches(cli_matches)?;
+        if let Some(skip_if_larger) = options.skip_if_larger
+            && !is_given("skip_if_larger")
+            && !is_given("no_skip_if_larger")
+        {
+            optimizer.skip_if_larger = skip_if_larger;
+            optimizer.no_skip_if_larger = !skip_if_larger;
+        }
+        optimizer
+            .config_args
+            .merge(options.config_args, cli_matches)?;
+
+        Ok(optimizer)
+    }
+
+    /// Returns options for each file, as indices into the returned optimizers, or `None` for files without
+    /// a configuration file. Files with the same configuration file and overrides share their options.
+    fn file_optimizers(
+        &self,
+        files: &[(PathBuf, Option<PathBuf>)],
+        cli_matches: &ArgMatches,
+    ) -> Result<(Vec<Self>, Vec<Option<usize>>)> {
+        let mut configs = HashMap::new();
+        let mut optimizer_indices = HashMap::new();
+        let mut optimizers = vec![];
+        let mut file_optimizers = vec![];
+
+        for (input_path, _) in files {
+            let Some(config_path) = self.config_path(input_path)? else {
+                file_optimizers.push(None);
+                continue;
+            };
+            let config_error =
+                |error: Error| Error::msg(format!("{}: {error}", config_path.display()));
+
+            let config = match configs.entry(config_path.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let config = ConfigFile::load(entry.key())?;
+                    entry.insert(config)
+                }
+            };
+            let overrides =
+                config.matching_overrides((!is_stdio(input_path)).then_some(input_path))?;
+
+            let index = match optimizer_indices.entry((config_path.clone(), overrides)) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    let options = config.options(&entry.key().1);
+                    optimizers.push(
+                        Self::configured_optimizer(options, cli_matches).map_err(config_error)?,
+                    );
+                    *entry.insert(optimizers.len() - 1)
+                }
+            };
+            file_optimizers.push(Some(index));
+        }
+
+        Ok((optimizers, file_optimizers))
+    }
+
     fn get_output_path(input_path: &Path, output_path_arg: Option<&Path>) -> Result<PathBuf> {
         if let Some(path) = output_path_arg {
             Ok(path.to_path_buf())
         } else {
-            let mut output_file_name = OsString::from("opt_");
+            let mut output_file_name = OsString::from(OUTPUT_PREFIX);
 
             if let Some(file_name) = input_path.file_name() {
                 output_file_name.push(file_name);
@@ -51,19 +298,131 @@ impl Optimizer {
         }
     }
 
-    fn optimize_file(&self, input_path: &Path, output_path_arg: Option<&Path>) -> Result<()> {
-        let file = File::open(input_path)?;
-        let file = BufReader::new(file);
-        let mut parser = Parser::new(file)?;
+    fn parse_file(config: &Config, input_path: &Path) -> Result<Vec<Node>> {
+        if is_stdio(input_path) {
+            Ok(config.parse_nodes(io::stdin().lock())?)
+        } else {
+            let file = File::open(input_path)?;
+            Ok(config.parse_nodes(BufReader::new(file))?)
+        }
+    }
+
+    fn read_file(input_path: &Path) -> Result<Vec<u8>> {
+        if is_stdio(input_path) {
+            let mut input = Vec::new();
+            io::stdin().lock().read_to_end(&mut input)?;
+            Ok(input)
+        } else {
+            Ok(fs::read(input_path)?)
+        }
+    }
+
+    fn writes_to_stdout(&self, input_path: &Path, output_path_arg: Option<&Path>) -> bool {
+        self.stdout || output_path_arg.map_or(is_stdio(input_path), is_stdio)
+    }
+
+    fn report_kind(&self) -> Option<ReportKind> {
+        match self.report_format {
+            ReportFormat::Text => self.report,
+            ReportFormat::Json => self.report.or(Some(ReportKind::Files)),
+        }
+    }
+
+    /// Returns the optimized nodes and reports of the applied optimizations.
+    fn apply_optimizations(
+        &self,
+        config: &Config,
+        nodes: Vec<Node>,
+    ) -> Result<(Vec<Node>, Vec<PassReport>)> {
+        Ok(config.apply_optimizations(nodes, self.report_kind() == Some(ReportKind::Passes))?)
+    }
+
+    fn skips_if_larger(&self) -> bool {
+        !self.no_skip_if_larger && (self.skip_if_larger || self.in_place || self.check)
+    }
+
+    /// Outputs are written to standard output only after all files are optimized, so that they are not interleaved.
+    fn optimize_file(
+        &self,
+        config: &Config,
+        input_path: &Path,
+        output_path_arg: Option<&Path>,
+    ) -> Result<OptimizedFile> {
+        let start = Instant::now();
+        let input = Self::read_file(input_path)?;
+        let nodes = config.parse_nodes(input.as_slice())?;
+        let (optimized, passes) = self.apply_optimizations(config, nodes)?;
 
-        let nodes = parser.parse_document()?;
-        let optimized = self.optimizations.apply(nodes, !self.disable_by_default);
+        let mut output = Vec::new();
+        config.write_nodes(&mut output, &optimized)?;
+
+        let input_size = input.len();
+        let skipped = self.skips_if_larger() && output.len() >= input_size;
+        let unchanged = skipped || output == input;
+        let output = if skipped { input } else { output };
+
+        let mut report = FileReport {
+            input_path: input_path.to_path_buf(),
+            input_size,
+            output_size: output.len(),
+            duration: start.elapsed(),
+            passes,
+            warnings: vec![],
+        };
+        if skipped {
+            report
+                .warnings
+                .push("optimized file is not smaller, kept the original".to_string());
+        }
+
+        if self.check {
+            let unoptimized = match self.check_threshold {
+                Some(threshold) => report::percentage_saved(input_size, output.len()) > threshold,
+                None => !unchanged,
+            };
+            if unoptimized {
+                report.warnings.push("file is not optimized".to_string());
+            }
+
+            return Ok(OptimizedFile {
+                stdout_output: None,
+                report,
+                unoptimized,
+            });
+        }
+
+        if self.writes_to_stdout(input_path, output_path_arg) {
+            return Ok(OptimizedFile {
+                stdout_output: Some(output),
+                report,
+                unoptimized: false,
+            });
+        }
 
         let output_path = Self::get_output_path(input_path, output_path_arg)?;
-        let output_file = File::create(output_path)?;
-        SVGWriter::new(output_file).write(optimized)?;
+        // The original file does not have to be rewritten to stay unchanged
+        if !(skipped && output_path == input_path) {
+            self.write_file(&output_path, &output)?;
+        }
+        report.duration = start.elapsed();
 
-        Ok(())
+        Ok(OptimizedFile {
+            stdout_output: None,
+            report,
+            unoptimized: false,
+        })
+    }
+
+    fn write_file(&self, output_path: &Path, output: &[u8]) -> Result<()> {
+        if let Some(output_dir) = output_path.parent() {
+            fs::create_dir_all(output_dir)?;
+        }
+
+        if self.backup {
+            fs::copy(output_path, with_suffix(output_path, ".bak"))?;
+        }
+
+        write_atomically(output_path, output)
     }
 
     fn validate_args(&self) -> Result<()> {
@@ -75,35 +434,222 @@ impl Optimizer {
             Err(Error::msg(
                 "There must be the same amount of output file paths and input file paths",
             ))
+        } else if self
+            .file_names
+            .iter()
+            .filter(|input_path| is_stdio(input_path))
+            .count()
+            > 1
+        {
+            Err(Error::msg("Standard input can only be given once"))
         } else {
             Ok(())
         }
     }
 
-    pub(crate) fn optimize(&self) -> Result<()> {
+    /// Pairs input files, including files found inside input directories, with their output paths.
+    fn collect_files(&self) -> Result<Vec<(PathBuf, Option<PathBuf>)>> {
+        let files = if self.output_file_names.is_empty() {
+            let mut files = vec![];
+            for input_path in &self.file_names {
+                if input_path.is_dir() {
+                    for relative_path in self.walk_options.walk(input_path)? {
+                        let file_path = input_path.join(&relative_path);
+                        let output_path = if self.in_place {
+                            Some(file_path.clone())
+                        } else {
+                            self.output_dir.as_ref().map(|dir| dir.join(relative_path))
+                        };
+                        files.push((file_path, output_path));
+                    }
+                } else if self.in_place && !is_stdio(input_path) {
+                    files.push((input_path.clone(), Some(input_path.clone())));
+                } else {
+                    let output_path = self
+                        .output_dir
+                        .as_ref()
+                        .zip(input_path.file_name())
+                        .filter(|_| !is_stdio(input_path))
+                        .map(|(dir, file_name)| dir.join(file_name));
+                    files.push((input_path.clone(), output_path));
+                }
+            }
+            files
+        } else {
+            self.file_names
+                .iter()
+                .cloned()
+                .zip(self.output_file_names.iter().cloned().map(Some))
+                .collect()
+        };
+
+        check_output_collisions(&files)?;
+        Ok(files)
+    }
+
+    fn lint_files(&self, config: &Config) -> Result<()> {
+        let findings = self
+            .collect_files()?
+            .into_par_iter()
+            .map(|(input_path, _)| {
+                let file_findings = lint(&Self::parse_file(config, &input_path)?);
+                Ok((input_path, file_findings))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut finding_count = 0;
+        for (input_path, file_findings) in findings {
+            for finding in file_findings {
+                println!("{}: {finding}", input_path.display());
+                finding_count += 1;
+            }
+        }
+
+        if finding_count == 0 {
+            Ok(())
+        } else {
+            Err(Error::msg(format!("Found {finding_count} id problems")))
+        }
+    }
+
+    fn list_passes() {
+        let width = ALL_OPTIMIZATIONS
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+
+        for (name, description) in ALL_OPTIMIZATIONS {
+            println!("{name:width$}  {description}");
+        }
+    }
+
+    /// Optimizes the files, with options from the command line matches and configuration files.
+    pub(crate) fn optimize(&self, cli_matches: &ArgMatches) -> Result<()> {
+        if self.list_passes {
+            Self::list_passes();
+            return Ok(());
+        }
+
         self.validate_args()?;
 
-        if self.output_file_names.is_empty() {
-            self.file_names
-                .par_iter()
-                .try_for_each(|input_path| self.optimize_file(input_path, None))?;
+        let config = self.config_args.config()?;
+        if self.lint {
+            return self.lint_files(&config);
+        }
+
+        let files = self.collect_files()?;
+        if self.report_format == ReportFormat::Json
+            && !self.check
+            && files.iter().any(|(input_path, output_path)| {
+                self.writes_to_stdout(input_path, output_path.as_deref())
+            })
+        {
+            return Err(Error::msg(
+                "JSON report cannot be written to standard output together with optimized files",
+            ));
+        }
+
+        let (optimizers, file_optimizers) = self.file_optimizers(&files, cli_matches)?;
+        let configs = optimizers
+            .iter()
+            .map(|optimizer| optimizer.config_args.config())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let results = files
+            .par_iter()
+            .zip(&file_optimizers)
+            .map(|((input_path, output_path), file_optimizer)| {
+                let (optimizer, config) =
+                    file_optimizer.map_or((self, &config), |index| (&optimizers[index], &configs[index]));
+                optimizer.optimize_file(config, input_path, output_path.as_deref())
+            })
+            .collect::<Vec<_>>();
+
+        let unoptimized_count = results
+            .iter()
+            .flatten()
+            .filter(|optimized_file| optimized_file.unoptimized)
+            .count();
+
+        match self.report_format {
+            ReportFormat::Text => self.finish_text(results.into_iter().collect::<Result<_>>()?)?,
+            ReportFormat::Json => Self::finish_json(files, results)?,
+        }
+
+        if unoptimized_count == 0 {
+            Ok(())
         } else {
-            self.file_names
-                .par_iter()
-                .zip(self.output_file_names.par_iter())
-                .try_for_each(|(input_path, output_path)| {
-                    self.optimize_file(input_path, Some(output_path))
-                })?;
+            Err(Error::msg(format!(
+                "Found {unoptimized_count} files which are not optimized"
+            )))
+        }
+    }
+
+    fn finish_text(&self, optimized_files: Vec<OptimizedFile>) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        let mut reports = vec![];
+        for optimized_file in optimized_files {
+            for warning in &optimized_file.report.warnings {
+                eprintln!("{}: {warning}", optimized_file.report.input_path.display());
+            }
+            if let Some(output) = optimized_file.stdout_output {
+                stdout.write_all(&output)?;
+            }
+            reports.push(optimized_file.report);
+        }
+
+        if self.report_kind().is_some() {
+            for report in &reports {
+                eprintln!("{report}");
+            }
+            if reports.len() > 1 {
+                eprintln!("{}", report::total(&reports));
+            }
         }
         Ok(())
     }
+
+    /// Files which could not be optimized are listed in the report instead of stopping at the first error.
+    fn finish_json(
+        files: Vec<(PathBuf, Option<PathBuf>)>,
+        results: Vec<Result<OptimizedFile>>,
+    ) -> Result<()> {
+        let mut reports = vec![];
+        let mut errors = vec![];
+        for ((input_path, _), result) in files.into_iter().zip(results) {
+            match result {
+                Ok(optimized_file) => reports.push(optimized_file.report),
+                Err(error) => errors.push((input_path, format!("{error:#}"))),
+            }
+        }
+
+        println!("{}", report::to_json(&reports, &errors));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::msg(format!(
+                "{} files could not be optimized",
+                errors.len()
+            )))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config_file::CONFIG_FILE_NAME;
+    use crate::optimizations::{DescriptionElement, Preset};
     use clap::Parser;
     use itertools::assert_equal;
+    use std::num::NonZeroUsize;
+
+    fn run(args: Vec<impl Into<OsString> + Clone>) -> Result<()> {
+        let cli_matches = Optimizer::command().try_get_matches_from(args)?;
+        Optimizer::from_arg_matches(&cli_matches)?.optimize(&cli_matches)
+    }
 
     #[test]
     fn test_parse_file_names_no_outputs() -> Result<()> {
@@ -121,9 +667,10 @@ mod tests {
             vec!["abc.svg", "somedir/xd.svg", "abcd.svg"],
         );
         assert!(optimizer.output_file_names.is_empty());
-        assert!(!optimizer.optimizations.no_remove_comments());
-        assert!(optimizer.optimizations.no_remove_attribute_whitespace());
-        assert!(optimizer.optimizations.no_remove_useless_groups());
+        let flags = &optimizer.config_args.flags;
+        assert_eq!(flags.enabled("remove_comments"), None);
+        assert_eq!(flags.enabled("remove_attribute_whitespace"), Some(false));
+        assert_eq!(flags.enabled("remove_useless_groups"), Some(false));
 
         Ok(())
     }
@@ -153,26 +700,24 @@ mod tests {
                 .map(|file| file.as_os_str()),
             vec!["abc2.svg", "somedir_321/xd.svg", "abcd.svg"],
         );
-        assert!(optimizer.optimizations.no_remove_comments());
-        assert!(!optimizer.optimizations.no_remove_attribute_whitespace());
-        assert!(!optimizer.optimizations.no_remove_useless_groups());
+        let flags = &optimizer.config_args.flags;
+        assert_eq!(flags.enabled("remove_comments"), Some(false));
+        assert_eq!(flags.enabled("remove_attribute_whitespace"), None);
+        assert_eq!(flags.enabled("remove_useless_groups"), None);
 
         Ok(())
     }
 
     #[test]
-    fn test_no_input_files_validation_error() -> Result<()> {
-        let optimizer = Optimizer::try_parse_from(vec!["main.exe"])?;
-
-        let result = optimizer.optimize();
+    fn test_no_input_files_validation_error() {
+        let result = run(vec!["main.exe"]);
 
         assert!(result.is_err());
-        Ok(())
     }
 
     #[test]
-    fn test_parse_file_names_validation_error() -> Result<()> {
-        let optimizer = Optimizer::try_parse_from(vec![
+    fn test_parse_file_names_validation_error() {
+        let result = run(vec![
             "main.exe",
             "abc.svg",
             "somedir/xd.svg",
@@ -181,11 +726,325 @@ mod tests {
             "abcd.svg",
             "--no-remove-comments",
             "abcd.svg",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_stdio_paths() -> Result<()> {
+        let optimizer =
+            Optimizer::try_parse_from(vec!["main.exe", "-", "abc.svg", "-o", "out.svg", "-"])?;
+
+        optimizer.validate_args()?;
+        assert!(optimizer.writes_to_stdout(Path::new("-"), None));
+        assert!(!optimizer.writes_to_stdout(Path::new("abc.svg"), None));
+        assert!(!optimizer.writes_to_stdout(Path::new("-"), Some(Path::new("out.svg"))));
+        assert!(optimizer.writes_to_stdout(Path::new("abc.svg"), Some(Path::new("-"))));
+
+        let optimizer = Optimizer::try_parse_from(vec!["main.exe", "abc.svg", "--stdout"])?;
+
+        assert!(optimizer.writes_to_stdout(Path::new("abc.svg"), None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdio_paths_validation_error() {
+        assert!(run(vec!["main.exe", "-", "-"]).is_err());
+        assert!(
+            Optimizer::try_parse_from(vec!["main.exe", "abc.svg", "-o", "-", "--stdout"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_collect_files_output_dir() -> Result<()> {
+        let optimizer = Optimizer::try_parse_from(vec![
+            "main.exe",
+            "somedir/abc.svg",
+            "-",
+            "--output-dir",
+            "out",
         ])?;
 
-        let result = optimizer.optimize();
+        assert_eq!(
+            optimizer.collect_files()?,
+            vec![
+                (
+                    PathBuf::from("somedir/abc.svg"),
+                    Some(PathBuf::from("out/abc.svg"))
+                ),
+                (PathBuf::from("-"), None),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_files_output_collision() -> Result<()> {
+        let optimizer = Optimizer::try_parse_from(vec![
+            "main.exe",
+            "first/abc.svg",
+            "second/abc.svg",
+            "--output-dir",
+            "out",
+        ])?;
+
+        assert!(optimizer.collect_files().is_err());
+
+        let optimizer =
+            Optimizer::try_parse_from(vec!["main.exe", "a.svg", "b.svg", "-o", "x.svg", "x.svg"])?;
+
+        assert!(optimizer.collect_files().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_place() -> Result<()> {
+        let root =
+            std::env::temp_dir().join(format!("svg-optimizer-in-place-{}", std::process::id()));
+        fs::create_dir_all(&root)?;
+        let valid_path = root.join("valid.svg");
+        let invalid_path = root.join("invalid.svg");
+        fs::write(
+            &valid_path,
+            r#"<svg>  <rect width="1" height="1"/>  </svg>"#,
+        )?;
+        fs::write(&invalid_path, "<svg><g></svg>")?;
+
+        let valid_result = run(vec![
+            "main.exe".as_ref(),
+            valid_path.as_os_str(),
+            "--in-place".as_ref(),
+            "--backup".as_ref(),
+        ]);
+        let invalid_result = run(vec![
+            "main.exe".as_ref(),
+            invalid_path.as_os_str(),
+            "--in-place".as_ref(),
+        ]);
+
+        let valid = fs::read_to_string(&valid_path);
+        let backup = fs::read_to_string(root.join("valid.svg.bak"));
+        let invalid = fs::read_to_string(&invalid_path);
+        let file_count = fs::read_dir(&root)?.count();
+        fs::remove_dir_all(&root)?;
+
+        assert!(valid_result.is_ok());
+        assert_eq!(valid?, r#"<svg><rect height="1" width="1"/></svg>"#);
+        assert_eq!(backup?, r#"<svg>  <rect width="1" height="1"/>  </svg>"#);
+        assert!(invalid_result.is_err());
+        assert_eq!(invalid?, "<svg><g></svg>");
+        assert_eq!(file_count, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_if_larger() -> Result<()> {
+        let root = std::env::temp_dir().join(format!(
+            "svg-optimizer-skip-if-larger-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&root)?;
+        let input_path = root.join("input.svg");
+        fs::write(&input_path, "<svg><rect height='1' width='1'/></svg>")?;
+
+        let optimize = |args: &[&str]| -> Result<String> {
+            let output_path = root.join("output.svg");
+            let mut all_args = vec!["main.exe".as_ref(), input_path.as_os_str()];
+            all_args.extend(args.iter().map(std::ffi::OsStr::new));
+            all_args.extend(["-o".as_ref(), output_path.as_os_str()]);
+            run(all_args)?;
+            Ok(fs::read_to_string(output_path)?)
+        };
+        let optimized = optimize(&[]);
+        let skipped = optimize(&["--skip-if-larger"]);
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(optimized?, r#"<svg><rect height="1" width="1"/></svg>"#);
+        assert_eq!(skipped?, "<svg><rect height='1' width='1'/></svg>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("svg-optimizer-check-{}", std::process::id()));
+        fs::create_dir_all(&root)?;
+        let optimized_path = root.join("optimized.svg");
+        let unoptimized_path = root.join("unoptimized.svg");
+        fs::write(
+            &optimized_path,
+            r#"<svg><rect height="1" width="1"/></svg>"#,
+        )?;
+        fs::write(
+            &unoptimized_path,
+            r#"<svg> <rect height="1" width="1"/> </svg>"#,
+        )?;
+
+        let check = |path: &Path, args: &[&str]| {
+            let mut all_args = vec!["main.exe".as_ref(), path.as_os_str(), "--check".as_ref()];
+            all_args.extend(args.iter().map(std::ffi::OsStr::new));
+            run(all_args)
+        };
+        let optimized_result = check(&optimized_path, &[]);
+        let larger_result = check(&optimized_path, &["--pretty"]);
+        let larger_not_skipped_result =
+            check(&optimized_path, &["--pretty", "--no-skip-if-larger"]);
+        let unoptimized_result = check(&unoptimized_path, &[]);
+        let threshold_result = check(&unoptimized_path, &["--check-threshold", "10"]);
+        let unoptimized = fs::read_to_string(&unoptimized_path);
+        let file_count = fs::read_dir(&root)?.count();
+        fs::remove_dir_all(&root)?;
+
+        assert!(optimized_result.is_ok());
+        assert!(larger_result.is_ok());
+        assert!(larger_not_skipped_result.is_err());
+        assert!(unoptimized_result.is_err());
+        assert!(threshold_result.is_ok());
+        assert_eq!(unoptimized?, r#"<svg> <rect height="1" width="1"/> </svg>"#);
+        assert_eq!(file_count, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multipass() -> Result<()> {
+        let input = r#"<svg><g fill="red"><rect width="1" height="1"/><rect width="0" height="1"/></g></svg>"#;
+        let optimize = |args: &[&str]| -> Result<String> {
+            let optimizer = Optimizer::try_parse_from([&["main.exe"], args].concat())?;
+            let config = optimizer.config_args.config()?;
+            let (nodes, _) =
+                optimizer.apply_optimizations(&config, config.parse_nodes(input.as_bytes())?)?;
+
+            let mut output = Vec::new();
+            config.write_nodes(&mut output, &nodes)?;
+            Ok(String::from_utf8(output)?)
+        };
+
+        assert_eq!(
+            optimize(&[])?,
+            r#"<svg><g fill="red"><rect height="1" width="1"/></g></svg>"#
+        );
+        assert_eq!(
+            optimize(&["--multipass"])?,
+            r#"<svg><rect fill="red" height="1" width="1"/></svg>"#
+        );
+        assert_eq!(
+            optimize(&["--multipass=1"])?,
+            r#"<svg><g fill="red"><rect height="1" width="1"/></g></svg>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_configured_optimizer() -> Result<()> {
+        let cli_matches = Optimizer::command().try_get_matches_from([
+            "main.exe",
+            "a.svg",
+            "--indent",
+            "2",
+            "--no-remove-comments",
+            "--preset",
+            "aggressive",
+        ])?;
+        let configured =
+            |source: &str| Optimizer::configured_optimizer(toml::from_str(source)?, &cli_matches);
+
+        let optimizer = configured(
+            "indent = 4\nmultipass = 3\nremove-comments = true\nskip-if-larger = true\nkeep-namespace = [\"a\", \"b\"]",
+        )?;
+        assert_eq!(optimizer.config_args.indent, Some(2));
+        assert_eq!(optimizer.config_args.multipass, NonZeroUsize::new(3));
+        assert_eq!(
+            optimizer.config_args.flags.enabled("remove_comments"),
+            Some(false)
+        );
+        assert!(optimizer.skip_if_larger);
+        assert_eq!(optimizer.file_names, vec![PathBuf::from("a.svg")]);
+
+        let optimizer = configured(concat!(
+            "preset = \"safe\"\nsvg2 = true\nskip-if-larger = false\n",
+            "keep-description = [\"title\"]\nmin-use-saving = 20\nremove-namespace = [\"a\"]",
+        ))?;
+        assert!(optimizer.no_skip_if_larger);
+        assert_eq!(
+            optimizer.config_args.config()?,
+            Config::default()
+                .preset(Preset::Aggressive)
+                .keep_description(DescriptionElement::Title)
+                .min_use_saving(20)
+                .remove_namespace("a")
+                .enable("convert_to_svg2")?
+                .disable("remove_comments")?
+                .indent(2)
+        );
+
+        assert!(configured("stdout = true").is_err());
+        assert!(configured("output-dir = \"out\"").is_err());
+        assert!(configured("recursive = true").is_err());
+        assert!(configured("unknown = 1").is_err());
+        assert!(configured("[options]\nindent = 1").is_err());
+        assert!(configured("remove-comments = 1").is_err());
+        assert!(configured("indent = \"4\"").is_err());
+        assert!(configured("preset = \"unknown\"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config() -> Result<()> {
+        let root =
+            std::env::temp_dir().join(format!("svg-optimizer-config-{}", std::process::id()));
+        fs::create_dir_all(root.join("raw"))?;
+        let input = "<svg><!--c--><g><rect height='1' width='1'/></g></svg>";
+        fs::write(root.join("a.svg"), input)?;
+        fs::write(root.join("raw/b.svg"), input)?;
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            "no-remove-comments = true\npretty = true\n\n[[overrides]]\nfiles = \"raw/*\"\npretty = false\n",
+        )?;
+
+        let optimize = |args: &[&str]| -> Result<Vec<String>> {
+            let output_paths = [root.join("out/a.svg"), root.join("out/b.svg")];
+            let mut all_args = vec![
+                OsString::from("main.exe"),
+                root.join("a.svg").into(),
+                root.join("raw/b.svg").into(),
+                "-o".into(),
+            ];
+            all_args.extend(output_paths.iter().map(OsString::from));
+            all_args.extend(args.iter().map(OsString::from));
+            run(all_args)?;
+
+            output_paths
+                .iter()
+                .map(|path| Ok(fs::read_to_string(path)?))
+                .collect()
+        };
+        let configured = optimize(&[]);
+        let overridden = optimize(&["--remove-comments"]);
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(
+            configured?,
+            vec![
+                "<svg>\n  <!-- c -->\n  <rect height=\"1\" width=\"1\"/>\n</svg>\n",
+                "<svg><!-- c --><rect height=\"1\" width=\"1\"/></svg>",
+            ]
+        );
+        assert_eq!(
+            overridden?,
+            vec![
+                "<svg>\n  <rect height=\"1\" width=\"1\"/>\n</svg>\n",
+                "<svg><rect height=\"1\" width=\"1\"/></svg>",
+            ]
+        );
 
-        assert!(result.is_err());
         Ok(())
     }
 }
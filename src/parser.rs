@@ -1,6 +1,7 @@
+use crate::error::ParseError;
 use crate::node::{ChildlessNodeType, Node, NodeNamespace};
-use anyhow::Result;
-use std::io::Read;
+use lazy_regex::{regex_captures, regex_is_match, regex_replace_all};
+use std::io::{Chain, Cursor, Read};
 use xml::name::OwnedName;
 use xml::{
     attribute::OwnedAttribute,
@@ -9,29 +10,102 @@ use xml::{
     EventReader,
 };
 
+type Result<T> = std::result::Result<T, ParseError>;
+
 /// Parses input stream of events provided by xml library into the internal node tree format.
 ///
-/// Because of library limitations, xml and DOCTYPE declarations are skipped.
+/// By default, xml and DOCTYPE declarations are skipped.
+/// If kept, DOCTYPE is placed right before the root element, as its original position is not known.
+/// Internal entities declared in DOCTYPE are always expanded, external entities are not supported.
 /// Whitespace between attributes, as well as their order, are lost as well.
 pub(crate) struct Parser<R: Read> {
-    source: EventReader<R>,
+    source: EventReader<Chain<Cursor<Vec<u8>>, R>>,
     curr_event: Option<XmlEvent>,
+    has_xml_declaration: bool,
+    declares_encoding: bool,
+    options: ParserOptions,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ParserOptions {
+    /// Keep the xml declaration, e.g. `<?xml version="1.0" encoding="UTF-8"?>`, if present.
+    pub(crate) keep_xml_declaration: bool,
+    /// Keep the DOCTYPE declaration, including its internal subset, if present.
+    pub(crate) keep_doctype: bool,
+    /// Remove entity declarations from the kept DOCTYPE. Entity references are always replaced with their values.
+    pub(crate) inline_entities: bool,
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const XML_DECLARATION_START: &[u8] = b"<?xml";
+
+// The library reports a document start even if there is no xml declaration, so it has to be checked for directly
+fn starts_with_xml_declaration(start: &[u8]) -> bool {
+    let start = start.strip_prefix(UTF8_BOM).unwrap_or(start);
+
+    start.starts_with(XML_DECLARATION_START)
+        && start
+            .get(XML_DECLARATION_START.len())
+            .is_some_and(u8::is_ascii_whitespace)
+}
+
+// The library reports an encoding even if the declaration does not have one, so the declaration is read up front
+fn read_xml_declaration(source: &mut impl Read, start: &mut Vec<u8>) -> Result<()> {
+    let mut byte = [0];
+    while !start.ends_with(b"?>") && source.read(&mut byte)? == 1 {
+        start.push(byte[0]);
+    }
+    Ok(())
+}
+
+fn declares_encoding(xml_declaration: &[u8]) -> bool {
+    regex_is_match!(r"\sencoding\s*=", &String::from_utf8_lossy(xml_declaration))
+}
+
+// External entities are expanded to empty strings by the library, which would silently corrupt the document
+fn find_external_entity(doctype: &str) -> Option<&str> {
+    regex_captures!(r#"<!ENTITY\s+(?:%\s+)?([^\s"']+)\s+(?:SYSTEM|PUBLIC)\s"#, doctype)
+        .map(|(_, name)| name)
+}
+
+fn remove_entity_declarations(doctype: &str) -> String {
+    let doctype = regex_replace_all!(r#"\s*<!ENTITY\s[^"'>]*(?:"[^"]*"|'[^']*')\s*>"#, doctype, "");
+    regex_replace_all!(r"\s*\[\s*\]", &doctype, "").into_owned()
 }
 
 impl<R: Read> Parser<R> {
-    pub(crate) fn new(source: R) -> Result<Self> {
+    pub(crate) fn new(mut source: R) -> Result<Self> {
+        let mut start = Vec::new();
+        source
+            .by_ref()
+            .take((UTF8_BOM.len() + XML_DECLARATION_START.len() + 1) as u64)
+            .read_to_end(&mut start)?;
+
+        let has_xml_declaration = starts_with_xml_declaration(&start);
+        if has_xml_declaration {
+            read_xml_declaration(&mut source, &mut start)?;
+        }
+
         let mut parser = Parser {
+            has_xml_declaration,
+            declares_encoding: has_xml_declaration && declares_encoding(&start),
             source: ParserConfig2::new()
                 .ignore_comments(false)
                 .whitespace_to_characters(true)
                 .ignore_root_level_whitespace(false)
-                .create_reader(source),
+                .create_reader(Cursor::new(start).chain(source)),
             curr_event: None,
+            options: ParserOptions::default(),
         };
         parser.next_event()?;
         Ok(parser)
     }
 
+    pub(crate) fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     fn next_event(&mut self) -> Result<()> {
         self.curr_event = match self.source.next()? {
             XmlEvent::EndDocument => None,
@@ -44,6 +118,10 @@ impl<R: Read> Parser<R> {
         let mut nodes = Vec::new();
 
         while self.curr_event.is_some() {
+            if let Some(doctype) = self.parse_doctype()? {
+                nodes.push(doctype);
+            }
+
             if let Some(node) = self.parse_node()? {
                 nodes.push(node);
             }
@@ -53,13 +131,49 @@ impl<R: Read> Parser<R> {
         Ok(nodes)
     }
 
+    // DOCTYPE is only available after the root element is reached
+    fn parse_doctype(&self) -> Result<Option<Node>> {
+        let Some(XmlEvent::StartElement { .. }) = self.curr_event else {
+            return Ok(None);
+        };
+        let Some(doctype) = self.source.doctype() else {
+            return Ok(None);
+        };
+
+        if let Some(name) = find_external_entity(doctype) {
+            return Err(ParseError::ExternalEntity(name.to_string()));
+        }
+
+        let doctype = if self.options.inline_entities {
+            remove_entity_declarations(doctype)
+        } else {
+            doctype.into()
+        };
+
+        Ok(self.options.keep_doctype.then_some(Node::ChildlessNode {
+            node_type: ChildlessNodeType::Doctype(doctype),
+        }))
+    }
+
     fn parse_node(&mut self) -> Result<Option<Node>> {
         if let Some(XmlEvent::EndElement { .. }) = self.curr_event {
             return Ok(None);
         }
 
         let node = match self.curr_event.take() {
-            Some(XmlEvent::StartDocument { .. }) => None,
+            Some(XmlEvent::StartDocument {
+                version,
+                standalone,
+                ..
+            }) => (self.options.keep_xml_declaration && self.has_xml_declaration).then_some(
+                Node::ChildlessNode {
+                    node_type: ChildlessNodeType::XmlDeclaration(
+                        version,
+                        self.declares_encoding,
+                        standalone,
+                    ),
+                },
+            ),
             Some(XmlEvent::ProcessingInstruction { name, data }) => Some(Node::ChildlessNode {
                 node_type: ChildlessNodeType::ProcessingInstruction(name, data),
             }),
@@ -133,7 +247,9 @@ impl<R: Read> Parser<R> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::Result;
     use crate::node::RegularNodeType;
+    use crate::writer::SVGWriter;
 
     #[test]
     fn test_parse_tag() -> Result<()> {
@@ -260,4 +376,125 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_keep_xml_declaration_and_doctype() -> Result<()> {
+        let test_string = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd" [
+    <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+]><svg xmlns="http://www.w3.org/2000/svg"/>"#;
+
+        let nodes = Parser::new(test_string.as_bytes())?
+            .with_options(ParserOptions {
+                keep_xml_declaration: true,
+                keep_doctype: true,
+                inline_entities: false,
+            })
+            .parse_document()?;
+
+        let mut writer = SVGWriter::new(Vec::new());
+        writer.write(&nodes)?;
+
+        assert_eq!(String::from_utf8(writer.into_inner())?, test_string);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_xml_declaration_encoding() -> Result<()> {
+        for (input, expected) in [
+            (
+                r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"/>"#,
+                r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"/>"#,
+            ),
+            (
+                r#"<?xml version="1.0" encoding="ISO-8859-1"?><svg xmlns="http://www.w3.org/2000/svg"/>"#,
+                r#"<?xml version="1.0" encoding="UTF-8"?><svg xmlns="http://www.w3.org/2000/svg"/>"#,
+            ),
+        ] {
+            let nodes = Parser::new(input.as_bytes())?
+                .with_options(ParserOptions {
+                    keep_xml_declaration: true,
+                    keep_doctype: false,
+                    inline_entities: false,
+                })
+                .parse_document()?;
+
+            let mut writer = SVGWriter::new(Vec::new());
+            writer.write(&nodes)?;
+
+            assert_eq!(String::from_utf8(writer.into_inner())?, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_missing_xml_declaration() -> Result<()> {
+        let test_string =
+            r#"<?xml-stylesheet href="style.css"?><svg xmlns="http://www.w3.org/2000/svg"/>"#;
+
+        let nodes = Parser::new(test_string.as_bytes())?
+            .with_options(ParserOptions {
+                keep_xml_declaration: true,
+                keep_doctype: true,
+                inline_entities: false,
+            })
+            .parse_document()?;
+
+        assert_eq!(nodes.len(), 2);
+        assert!(matches!(
+            nodes[0],
+            Node::ChildlessNode {
+                node_type: ChildlessNodeType::ProcessingInstruction(..)
+            }
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_entities() -> Result<()> {
+        let test_string = r#"<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd" [
+    <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+    <!ENTITY st0 'fill:#FF0000;'>
+]>
+<svg xmlns="&ns_svg;"><rect style="&st0;"/></svg>"#;
+
+        let nodes = Parser::new(test_string.as_bytes())?
+            .with_options(ParserOptions {
+                keep_xml_declaration: false,
+                keep_doctype: true,
+                inline_entities: true,
+            })
+            .parse_document()?;
+
+        let mut writer = SVGWriter::new(Vec::new());
+        writer.write(&nodes)?;
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner())?,
+            r#"
+
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd"><svg xmlns="http://www.w3.org/2000/svg"><rect style="fill:#FF0000;"/></svg>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_entity() -> Result<()> {
+        let test_string = r#"<!DOCTYPE svg [
+    <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+    <!ENTITY st0 SYSTEM "style.txt">
+]>
+<svg xmlns="&ns_svg;"><rect style="&st0;"/></svg>"#;
+
+        let mut parser = Parser::new(test_string.as_bytes())?;
+
+        assert!(parser.parse_document().is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,265 @@
+use crate::optimizations::Parameter;
+use itertools::Itertools;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Level of detail of the size report.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ReportKind {
+    /// Sizes of each file.
+    Files,
+    /// Sizes of each file and bytes saved by each optimization.
+    Passes,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ReportFormat {
+    /// Human-readable lines written to standard error.
+    #[default]
+    Text,
+    /// A single JSON document written to standard output.
+    Json,
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn percentage_saved(size_before: usize, size_after: usize) -> f64 {
+    if size_before == 0 {
+        0.0
+    } else {
+        (size_before as f64 - size_after as f64) / size_before as f64 * 100.0
+    }
+}
+
+fn bytes_saved(size_before: usize, size_after: usize) -> String {
+    if size_after > size_before {
+        format!("-{}", size_after - size_before)
+    } else {
+        (size_before - size_after).to_string()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn serialize_milliseconds<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_nanos() as f64 / 1_000_000.0)
+}
+
+fn serialize_path<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// Sizes of the document serialized without pretty printing before and after an optimization.
+#[derive(Clone, Copy, Serialize)]
+pub(crate) struct PassSizes {
+    pub(crate) size_before: usize,
+    pub(crate) size_after: usize,
+}
+
+#[derive(Serialize)]
+pub(crate) struct PassReport {
+    pub(crate) name: &'static str,
+    /// Parameters the optimization was applied with, without the ones which are not set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) parameters: Vec<Parameter>,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_milliseconds")]
+    pub(crate) duration: Duration,
+    /// Measured only if passes are reported.
+    #[serde(flatten)]
+    pub(crate) sizes: Option<PassSizes>,
+}
+
+impl fmt::Display for PassReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.parameters.is_empty() {
+            let parameters = self
+                .parameters
+                .iter()
+                .map(|parameter| format!("{}={}", parameter.name, parameter.value))
+                .join(", ");
+            write!(f, " ({parameters})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct FileReport {
+    #[serde(rename = "path", serialize_with = "serialize_path")]
+    pub(crate) input_path: PathBuf,
+    pub(crate) input_size: usize,
+    pub(crate) output_size: usize,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_milliseconds")]
+    pub(crate) duration: Duration,
+    pub(crate) passes: Vec<PassReport>,
+    pub(crate) warnings: Vec<String>,
+}
+
+impl fmt::Display for FileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} bytes ({:.2}% saved)",
+            self.input_path.display(),
+            self.input_size,
+            self.output_size,
+            percentage_saved(self.input_size, self.output_size)
+        )?;
+
+        for pass in &self.passes {
+            if let Some(PassSizes {
+                size_before,
+                size_after,
+            }) = pass.sizes
+            {
+                write!(
+                    f,
+                    "\n  {pass}: {} bytes saved",
+                    bytes_saved(size_before, size_after)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct TotalReport {
+    input_size: usize,
+    output_size: usize,
+}
+
+impl TotalReport {
+    fn of(reports: &[FileReport]) -> Self {
+        Self {
+            input_size: reports.iter().map(|report| report.input_size).sum(),
+            output_size: reports.iter().map(|report| report.output_size).sum(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorReport<'a> {
+    #[serde(serialize_with = "serialize_path")]
+    path: &'a Path,
+    message: &'a str,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    files: &'a [FileReport],
+    errors: Vec<ErrorReport<'a>>,
+    total: TotalReport,
+}
+
+/// Sums up the sizes of all files.
+pub(crate) fn total(reports: &[FileReport]) -> String {
+    let TotalReport {
+        input_size,
+        output_size,
+    } = TotalReport::of(reports);
+
+    format!(
+        "Total: {input_size} -> {output_size} bytes ({:.2}% saved)",
+        percentage_saved(input_size, output_size)
+    )
+}
+
+/// Serializes reports of optimized files and errors of files which could not be optimized.
+pub(crate) fn to_json(reports: &[FileReport], errors: &[(PathBuf, String)]) -> String {
+    let report = JsonReport {
+        files: reports,
+        errors: errors
+            .iter()
+            .map(|(path, message)| ErrorReport { path, message })
+            .collect(),
+        total: TotalReport::of(reports),
+    };
+
+    serde_json::to_string(&report)
+        .unwrap_or_else(|_| unreachable!("reports are always serializable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizations::ParameterValue;
+
+    fn file_report() -> FileReport {
+        FileReport {
+            input_path: PathBuf::from("icons/a.svg"),
+            input_size: 400,
+            output_size: 300,
+            duration: Duration::from_micros(2500),
+            passes: vec![
+                PassReport {
+                    name: "remove_comments",
+                    parameters: vec![],
+                    duration: Duration::from_micros(500),
+                    sizes: Some(PassSizes {
+                        size_before: 380,
+                        size_after: 290,
+                    }),
+                },
+                PassReport {
+                    name: "convert_paths_to_uses",
+                    parameters: vec![Parameter {
+                        name: "min_use_saving",
+                        value: ParameterValue::Number(20),
+                    }],
+                    duration: Duration::from_millis(1),
+                    sizes: Some(PassSizes {
+                        size_before: 290,
+                        size_after: 300,
+                    }),
+                },
+            ],
+            warnings: vec![],
+        }
+    }
+
+    #[test]
+    fn test_file_report() {
+        let report = file_report();
+
+        assert_eq!(
+            report.to_string(),
+            "icons/a.svg: 400 -> 300 bytes (25.00% saved)\n  remove_comments: 90 bytes saved\n  convert_paths_to_uses (min_use_saving=20): -10 bytes saved"
+        );
+        assert_eq!(total(&[report]), "Total: 400 -> 300 bytes (25.00% saved)");
+    }
+
+    #[test]
+    fn test_json_report() {
+        let mut report = file_report();
+        report.passes[0].sizes = None;
+        report.passes[1].parameters.push(Parameter {
+            name: "keep_namespace",
+            value: ParameterValue::List(vec!["http://example.com/\"a\"".to_string()]),
+        });
+        report.warnings.push("kept \"the\" original".to_string());
+
+        assert_eq!(
+            to_json(
+                &[report],
+                &[(
+                    PathBuf::from("b.svg"),
+                    "Unexpected end\nof file".to_string()
+                )]
+            ),
+            concat!(
+                r#"{"files":[{"path":"icons/a.svg","input_size":400,"output_size":300,"duration_ms":2.5,"#,
+                r#""passes":[{"name":"remove_comments","duration_ms":0.5},{"name":"convert_paths_to_uses","#,
+                r#""parameters":[{"name":"min_use_saving","value":20},"#,
+                r#"{"name":"keep_namespace","value":["http://example.com/\"a\""]}],"#,
+                r#""duration_ms":1.0,"size_before":290,"size_after":300}],"warnings":["kept \"the\" original"]}],"#,
+                r#""errors":[{"path":"b.svg","message":"Unexpected end\nof file"}],"#,
+                r#""total":{"input_size":400,"output_size":300}}"#
+            )
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use anyhow::{Error, Result};
+use itertools::Itertools;
+use lazy_regex::{Regex, regex::escape};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Prefix of output file names when no output path is given, which are skipped when walking directories unless
+/// `--no-default-exclude` is given.
+pub(crate) const OUTPUT_PREFIX: &str = "opt_";
+
+/// Shell-style pattern matched against paths relative to the walked directory.
+///
+/// Supports `*`, `**`, `?`, `[...]` and `{a,b}`. Patterns without `/` are matched against file names only.
+#[derive(Clone, Debug)]
+pub(crate) struct Glob {
+    regex: Regex,
+    matches_file_name: bool,
+}
+
+impl FromStr for Glob {
+    type Err = Error;
+
+    fn from_str(pattern: &str) -> Result<Self> {
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        let mut brace_depth = 0;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.next_if_eq(&'*').is_some() => {
+                    if chars.next_if_eq(&'/').is_some() {
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => {
+                    regex.push('[');
+                    if chars.next_if_eq(&'!').is_some() {
+                        regex.push('^');
+                    }
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('-') => regex.push('-'),
+                            Some(c) => regex.push_str(&escape(&c.to_string())),
+                            None => return Err(Error::msg(format!("Unclosed `[` in `{pattern}`"))),
+                        }
+                    }
+                    regex.push(']');
+                }
+                '{' => {
+                    brace_depth += 1;
+                    regex.push_str("(?:");
+                }
+                ',' if brace_depth > 0 => regex.push('|'),
+                '}' if brace_depth > 0 => {
+                    brace_depth -= 1;
+                    regex.push(')');
+                }
+                c => regex.push_str(&escape(&c.to_string())),
+            }
+        }
+
+        if brace_depth > 0 {
+            return Err(Error::msg(format!("Unclosed `{{` in `{pattern}`")));
+        }
+        regex.push('$');
+
+        Ok(Self {
+            regex: Regex::new(&regex)?,
+            matches_file_name: !pattern.contains('/'),
+        })
+    }
+}
+
+impl Glob {
+    pub(crate) fn is_match(&self, relative_path: &Path) -> bool {
+        let path = if self.matches_file_name {
+            relative_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        } else {
+            relative_path
+                .iter()
+                .map(|component| component.to_string_lossy())
+                .join("/")
+        };
+
+        self.regex.is_match(&path)
+    }
+}
+
+#[derive(clap::Args)]
+pub(crate) struct WalkOptions {
+    /// Optimize files inside directories given as input, including their subdirectories.
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Only optimize files in input directories whose paths match one of the patterns, e.g. `icons/**/*.svg`.
+    /// Patterns without `/` are matched against file names.
+    #[arg(long, value_name = "GLOB", default_value = "*.svg")]
+    include: Vec<Glob>,
+
+    /// Skip files and directories in input directories whose paths match one of the patterns. Outputs of previous
+    /// runs named `opt_{original_filename}` are always skipped as well.
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<Glob>,
+
+    /// Don't skip files and directories named `opt_*` in input directories.
+    #[arg(long)]
+    no_default_exclude: bool,
+
+    /// Follow symbolic links inside input directories. By default, they are skipped.
+    #[arg(long)]
+    follow_symlinks: bool,
+}
+
+impl WalkOptions {
+    fn is_excluded(&self, relative_path: &Path) -> bool {
+        let is_output = relative_path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(OUTPUT_PREFIX));
+
+        (is_output && !self.no_default_exclude)
+            || self.exclude.iter().any(|glob| glob.is_match(relative_path))
+    }
+
+    /// Returns paths of files to optimize inside the directory, relative to it and sorted alphabetically.
+    pub(crate) fn walk(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        if !self.recursive {
+            return Err(Error::msg(format!(
+                "{} is a directory, use --recursive to optimize files inside it",
+                dir.display()
+            )));
+        }
+
+        let mut files = vec![];
+        self.walk_dir(dir, Path::new(""), &mut HashSet::new(), &mut files)?;
+        Ok(files)
+    }
+
+    fn walk_dir(
+        &self,
+        root: &Path,
+        relative_dir: &Path,
+        visited_dirs: &mut HashSet<PathBuf>,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let dir = root.join(relative_dir);
+
+        // Symbolic links may lead to a directory which is already being walked
+        if !visited_dirs.insert(fs::canonicalize(&dir)?) {
+            return Ok(());
+        }
+
+        let mut names = fs::read_dir(&dir)?
+            .map(|entry| Ok(entry?.file_name()))
+            .collect::<Result<Vec<_>>>()?;
+        names.sort();
+
+        for name in names {
+            let relative_path = relative_dir.join(name);
+            let path = root.join(&relative_path);
+
+            if self.is_excluded(&relative_path) {
+                continue;
+            }
+
+            let mut metadata = fs::symlink_metadata(&path)?;
+            if metadata.is_symlink() {
+                if !self.follow_symlinks {
+                    continue;
+                }
+                metadata = fs::metadata(&path)?;
+            }
+
+            if metadata.is_dir() {
+                self.walk_dir(root, &relative_path, visited_dirs, files)?;
+            } else if self
+                .include
+                .iter()
+                .any(|glob| glob.is_match(&relative_path))
+            {
+                files.push(relative_path);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(clap::Parser)]
+    struct TestArgs {
+        #[command(flatten)]
+        walk_options: WalkOptions,
+    }
+
+    fn walk_options(args: &[&str]) -> WalkOptions {
+        TestArgs::parse_from([&["main.exe"], args].concat()).walk_options
+    }
+
+    fn create_files(root: &Path, relative_paths: &[&str]) -> Result<()> {
+        for relative_path in relative_paths {
+            let path = root.join(relative_path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, "<svg/>")?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob() -> Result<()> {
+        let glob: Glob = "*.svg".parse()?;
+        assert!(glob.is_match(Path::new("a.svg")));
+        assert!(glob.is_match(Path::new("icons/a.svg")));
+        assert!(!glob.is_match(Path::new("a.svgz")));
+
+        let glob: Glob = "icons/**/[!_]*.{svg,xml}".parse()?;
+        assert!(glob.is_match(Path::new("icons/a.svg")));
+        assert!(glob.is_match(Path::new("icons/small/b.xml")));
+        assert!(!glob.is_match(Path::new("icons/_c.svg")));
+        assert!(!glob.is_match(Path::new("other/icons/a.svg")));
+
+        assert!("[a".parse::<Glob>().is_err());
+        assert!("{a,b".parse::<Glob>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("svg-optimizer-walk-{}", std::process::id()));
+        create_files(
+            &root,
+            &[
+                "b.svg",
+                "a.svg",
+                "notes.txt",
+                "icons/c.svg",
+                "icons/large/d.svg",
+                "build/e.svg",
+                "opt_a.svg",
+            ],
+        )?;
+
+        let files = walk_options(&["-r", "--exclude", "build"]).walk(&root);
+        let default_files = walk_options(&["-r"]).walk(&root);
+        let output_files =
+            walk_options(&["-r", "--no-default-exclude", "--exclude", "icons"]).walk(&root);
+        let filtered_files = walk_options(&["-r", "--include", "icons/**/*.svg"]).walk(&root);
+        let not_recursive = walk_options(&[]).walk(&root);
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(
+            files?,
+            vec![
+                PathBuf::from("a.svg"),
+                PathBuf::from("b.svg"),
+                PathBuf::from("icons/c.svg"),
+                PathBuf::from("icons/large/d.svg"),
+            ]
+        );
+        assert_eq!(
+            default_files?,
+            vec![
+                PathBuf::from("a.svg"),
+                PathBuf::from("b.svg"),
+                PathBuf::from("build/e.svg"),
+                PathBuf::from("icons/c.svg"),
+                PathBuf::from("icons/large/d.svg"),
+            ]
+        );
+        assert_eq!(
+            output_files?,
+            vec![
+                PathBuf::from("a.svg"),
+                PathBuf::from("b.svg"),
+                PathBuf::from("build/e.svg"),
+                PathBuf::from("opt_a.svg"),
+            ]
+        );
+        assert_eq!(
+            filtered_files?,
+            vec![
+                PathBuf::from("icons/c.svg"),
+                PathBuf::from("icons/large/d.svg")
+            ]
+        );
+        assert!(not_recursive.is_err());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_symlinks() -> Result<()> {
+        let root =
+            std::env::temp_dir().join(format!("svg-optimizer-symlinks-{}", std::process::id()));
+        create_files(&root, &["icons/a.svg"])?;
+        std::os::unix::fs::symlink(root.join("icons"), root.join("icons/loop"))?;
+        std::os::unix::fs::symlink(root.join("icons/a.svg"), root.join("b.svg"))?;
+
+        let files = walk_options(&["-r"]).walk(&root);
+        let followed_files = walk_options(&["-r", "--follow-symlinks"]).walk(&root);
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(files?, vec![PathBuf::from("icons/a.svg")]);
+        assert_eq!(
+            followed_files?,
+            vec![PathBuf::from("b.svg"), PathBuf::from("icons/a.svg")]
+        );
+
+        Ok(())
+    }
+}
@@ -1,36 +1,419 @@
-use std::io::Write;
-use xml::{writer::Error, EmitterConfig, EventWriter};
+use std::io::{Result, Write};
+use xml::attribute::OwnedAttribute;
+use xml::namespace::{NS_EMPTY_URI, NS_NO_PREFIX, NS_XML_PREFIX, NS_XMLNS_PREFIX, NamespaceStack};
 
-use crate::node::Node;
+use crate::node::{ChildlessNodeType, Node, NodeNamespace, RegularNodeType};
+use crate::optimizations::common::constants::{PRESERVE_VAL, SPACE_NAME, XML_PREFIX};
 
+const DEFAULT_INDENT: usize = 2;
+
+/// Writes the internal node tree format into the output stream.
+///
+/// Special characters are only escaped where they would otherwise change the meaning of the document.
+/// Each attribute value is quoted with the quote character which requires fewer escapes.
 pub(crate) struct SVGWriter<W: Write> {
-    writer: EventWriter<W>,
+    target: W,
+    namespaces: NamespaceStack,
+    options: WriterOptions,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct WriterOptions {
+    /// Write each element on a separate line, indented according to its depth.
+    pub(crate) pretty: bool,
+    /// Number of spaces used for a single level of indentation. Implies `pretty`.
+    pub(crate) indent: Option<usize>,
+}
+
+impl WriterOptions {
+    fn indentation(&self) -> Option<usize> {
+        self.indent
+            .or_else(|| self.pretty.then_some(DEFAULT_INDENT))
+    }
+}
+
+fn is_whitespace(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::ChildlessNode {
+            node_type: ChildlessNodeType::Text(text, false),
+        } if text.trim().is_empty()
+    )
+}
+
+fn is_text(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::ChildlessNode {
+            node_type: ChildlessNodeType::Text(..),
+        }
+    )
+}
+
+// Whitespace between children of these elements may be rendered, so they cannot be indented
+fn is_whitespace_significant(
+    node_type: &RegularNodeType,
+    attributes: &[OwnedAttribute],
+    children: &[Node],
+) -> bool {
+    matches!(
+        node_type,
+        RegularNodeType::Text
+            | RegularNodeType::TSpan
+            | RegularNodeType::TextPath
+            | RegularNodeType::ForeignObject
+    ) || attributes.iter().any(|attr| {
+        attr.name.prefix.as_deref() == Some(XML_PREFIX)
+            && attr.name.local_name == SPACE_NAME
+            && attr.value == PRESERVE_VAL
+    }) || children
+        .iter()
+        .any(|child| is_text(child) && !is_whitespace(child))
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            // `]]>` is not allowed in text
+            '>' if escaped.ends_with("]]") => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn choose_quote(value: &str) -> char {
+    if value.matches('"').count() > value.matches('\'').count() {
+        '\''
+    } else {
+        '"'
+    }
+}
+
+fn escape_attribute_value(value: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            // Whitespace characters other than space would be normalized to spaces when parsing
+            '\t' => escaped.push_str("&#9;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '"' if quote == '"' => escaped.push_str("&#34;"),
+            '\'' if quote == '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// `]]>` cannot appear inside of CDATA, so it is split between two sections
+fn escape_cdata(text: &str) -> String {
+    text.replace("]]>", "]]]]><![CDATA[>")
+}
+
+fn element_name(node_type: &RegularNodeType, namespace: &NodeNamespace) -> String {
+    match &namespace.prefix {
+        Some(prefix) => format!("{prefix}:{node_type}"),
+        None => node_type.to_string(),
+    }
 }
 
 impl<W: Write> SVGWriter<W> {
     pub(crate) fn new(target: W) -> Self {
-        let mut config = EmitterConfig::new()
-            .write_document_declaration(false)
-            .pad_self_closing(false);
+        Self {
+            target,
+            namespaces: NamespaceStack::empty(),
+            options: WriterOptions::default(),
+        }
+    }
 
-        config.perform_escaping = false;
+    pub(crate) fn with_options(mut self, options: WriterOptions) -> Self {
+        self.options = options;
+        self
+    }
 
-        Self {
-            writer: config.create_writer(target),
+    pub(crate) fn write(&mut self, nodes: &[Node]) -> Result<()> {
+        if self.options.indentation().is_some() {
+            nodes
+                .iter()
+                .filter(|node| !is_whitespace(node))
+                .try_for_each(|node| {
+                    self.write_node(node, Some(0))?;
+                    writeln!(self.target)
+                })
+        } else {
+            self.write_nodes(nodes, None)
         }
     }
 
-    pub(crate) fn write(&mut self, nodes: Vec<Node>) -> Result<(), Error> {
-        nodes.into_iter().try_for_each(|node| {
-            node.into_iter()
-                .try_for_each(|event| self.writer.write(event.as_writer_event().unwrap()))
-        })?;
+    /// Writes the nodes one per line at the given depth, or as they are if depth is not given.
+    fn write_nodes(&mut self, nodes: &[Node], depth: Option<usize>) -> Result<()> {
+        nodes.iter().try_for_each(|node| match depth {
+            Some(_) if is_whitespace(node) => Ok(()),
+            Some(depth) => {
+                self.write_indentation(depth)?;
+                self.write_node(node, Some(depth))
+            }
+            None => self.write_node(node, None),
+        })
+    }
+
+    fn write_indentation(&mut self, depth: usize) -> Result<()> {
+        let indent = self.options.indentation().unwrap_or_default();
+        write!(self.target, "\n{}", " ".repeat(depth * indent))
+    }
+
+    fn write_node(&mut self, node: &Node, depth: Option<usize>) -> Result<()> {
+        match node {
+            Node::RegularNode {
+                node_type,
+                namespace,
+                attributes,
+                children,
+            } => self.write_element(node_type, namespace, attributes, children, depth),
+            Node::ChildlessNode { node_type } => self.write_childless_node(node_type),
+        }
+    }
+
+    fn write_element(
+        &mut self,
+        node_type: &RegularNodeType,
+        namespace: &NodeNamespace,
+        attributes: &[OwnedAttribute],
+        children: &[Node],
+        depth: Option<usize>,
+    ) -> Result<()> {
+        let name = element_name(node_type, namespace);
+        write!(self.target, "<{name}")?;
+
+        let inherits_default_namespace = self
+            .namespaces
+            .get(NS_NO_PREFIX)
+            .is_some_and(|uri| uri != NS_EMPTY_URI);
+        self.namespaces
+            .push_empty()
+            .checked_target()
+            .extend(&namespace.element_namespace);
+        self.write_namespace_declarations(inherits_default_namespace)?;
+
+        for attr in attributes {
+            self.write_attribute(&attr.name.borrow().repr_display().to_string(), &attr.value)?;
+        }
+
+        let children_depth = depth
+            .filter(|_| !is_whitespace_significant(node_type, attributes, children))
+            .map(|depth| depth + 1);
+
+        if children.is_empty() || (children_depth.is_some() && children.iter().all(is_whitespace)) {
+            write!(self.target, "/>")?;
+        } else {
+            write!(self.target, ">")?;
+            self.write_nodes(children, children_depth)?;
+            if let (Some(depth), Some(_)) = (depth, children_depth) {
+                self.write_indentation(depth)?;
+            }
+            write!(self.target, "</{name}>")?;
+        }
 
+        self.namespaces.pop();
         Ok(())
     }
 
+    // Only namespaces which are not already in scope are declared. The empty default namespace is only declared to
+    // undeclare the default namespace of an ancestor.
+    fn write_namespace_declarations(&mut self, inherits_default_namespace: bool) -> Result<()> {
+        let declarations: Vec<(String, String)> = self
+            .namespaces
+            .peek()
+            .into_iter()
+            .filter_map(|(prefix, uri)| match prefix {
+                NS_XMLNS_PREFIX | NS_XML_PREFIX => None,
+                NS_NO_PREFIX => (uri != NS_EMPTY_URI || inherits_default_namespace)
+                    .then(|| (NS_XMLNS_PREFIX.into(), uri.into())),
+                prefix => Some((format!("{NS_XMLNS_PREFIX}:{prefix}"), uri.into())),
+            })
+            .collect();
+
+        declarations
+            .iter()
+            .try_for_each(|(name, uri)| self.write_attribute(name, uri))
+    }
+
+    fn write_attribute(&mut self, name: &str, value: &str) -> Result<()> {
+        let quote = choose_quote(value);
+        let value = escape_attribute_value(value, quote);
+
+        write!(self.target, " {name}={quote}{value}{quote}")
+    }
+
+    fn write_childless_node(&mut self, node_type: &ChildlessNodeType) -> Result<()> {
+        match node_type {
+            ChildlessNodeType::XmlDeclaration(version, declares_encoding, standalone) => {
+                write!(self.target, r#"<?xml version="{version}""#)?;
+                // The output is always written as UTF-8, whatever the encoding of the input was
+                if *declares_encoding {
+                    write!(self.target, r#" encoding="UTF-8""#)?;
+                }
+                if let Some(standalone) = standalone {
+                    let standalone = if *standalone { "yes" } else { "no" };
+                    write!(self.target, r#" standalone="{standalone}""#)?;
+                }
+                write!(self.target, "?>")
+            }
+            ChildlessNodeType::Doctype(doctype) => write!(self.target, "{doctype}"),
+            ChildlessNodeType::ProcessingInstruction(name, data) => match data {
+                Some(data) => write!(self.target, "<?{name} {data}?>"),
+                None => write!(self.target, "<?{name}?>"),
+            },
+            ChildlessNodeType::Comment(text) => {
+                let start_padding = if text.starts_with(char::is_whitespace) {
+                    ""
+                } else {
+                    " "
+                };
+                let end_padding = if text.ends_with(char::is_whitespace) {
+                    ""
+                } else {
+                    " "
+                };
+                write!(self.target, "<!--{start_padding}{text}{end_padding}-->")
+            }
+            ChildlessNodeType::Text(text, true) => {
+                write!(self.target, "<![CDATA[{}]]>", escape_cdata(text))
+            }
+            ChildlessNodeType::Text(text, false) => write!(self.target, "{}", escape_text(text)),
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn into_inner(self) -> W {
-        self.writer.into_inner()
+        self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use std::fs;
+
+    fn write_nodes(nodes: &[Node]) -> anyhow::Result<String> {
+        let mut writer = SVGWriter::new(Vec::new());
+        writer.write(nodes)?;
+
+        Ok(String::from_utf8(writer.into_inner())?)
+    }
+
+    // Output has to be parsed into the same node tree as the input
+    fn round_trip(input: &str) -> anyhow::Result<String> {
+        let nodes = Parser::new(input.as_bytes())?.parse_document()?;
+        let output = write_nodes(&nodes)?;
+
+        let reparsed_nodes = Parser::new(output.as_bytes())?.parse_document()?;
+        assert_eq!(reparsed_nodes, nodes);
+
+        Ok(output)
+    }
+
+    #[test]
+    fn test_escape_text() -> anyhow::Result<()> {
+        let output = round_trip(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><text>a &amp; b &lt; c &gt; "d" ]]&gt;</text></svg>"#,
+        )?;
+
+        assert_eq!(
+            output,
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><text>a &amp; b &lt; c > "d" ]]&gt;</text></svg>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_attributes() -> anyhow::Result<()> {
+        let output = round_trip(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" font-family='"Times New Roman"' aria-label="it's" title="&quot;&quot;'&amp;&lt;>" data-lines="a&#10;b"/>"#,
+        )?;
+
+        assert_eq!(
+            output,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" font-family='"Times New Roman"' aria-label="it's" title='""&#39;&amp;&lt;>' data-lines="a&#10;b"/>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undeclare_default_namespace() -> anyhow::Result<()> {
+        let source = r#"<svg xmlns="http://www.w3.org/2000/svg"><foreignObject><data xmlns=""><item/></data></foreignObject></svg>"#;
+
+        assert_eq!(round_trip(source)?, source);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_cdata() -> anyhow::Result<()> {
+        let nodes = vec![Node::ChildlessNode {
+            node_type: ChildlessNodeType::Text("a]]>b".into(), true),
+        }];
+
+        assert_eq!(write_nodes(&nodes)?, "<![CDATA[a]]]]><![CDATA[>b]]>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty() -> anyhow::Result<()> {
+        let test_string = r#"<?xml-stylesheet href="style.css"?>
+<svg xmlns="http://www.w3.org/2000/svg"><!--comment--><g><g>
+</g><text x="1"><tspan>A</tspan> <tspan>B</tspan></text><g xml:space="preserve"><rect/>  <rect/></g><style>rect { fill: red; }</style></g></svg>"#;
+        let nodes = Parser::new(test_string.as_bytes())?.parse_document()?;
+
+        let mut writer = SVGWriter::new(Vec::new()).with_options(WriterOptions {
+            pretty: true,
+            indent: Some(4),
+        });
+        writer.write(&nodes)?;
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner())?,
+            r#"<?xml-stylesheet href="style.css"?>
+<svg xmlns="http://www.w3.org/2000/svg">
+    <!-- comment -->
+    <g>
+        <g/>
+        <text x="1"><tspan>A</tspan> <tspan>B</tspan></text>
+        <g xml:space="preserve"><rect/>  <rect/></g>
+        <style>rect { fill: red; }</style>
+    </g>
+</svg>
+"#
+        );
+
+        Ok(())
+    }
+
+    // Comments are padded and whitespace is split differently when reparsed, so only the written output is compared
+    #[test]
+    fn test_round_trip_examples() -> anyhow::Result<()> {
+        for entry in fs::read_dir("examples")? {
+            let input = fs::read_to_string(entry?.path())?;
+            let output = write_nodes(&Parser::new(input.as_bytes())?.parse_document()?)?;
+
+            let reparsed_output = write_nodes(&Parser::new(output.as_bytes())?.parse_document()?)?;
+            assert_eq!(reparsed_output, output);
+        }
+
+        Ok(())
     }
 }